@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+
+/// Decimals of every token mint created at launch
+#[constant]
+pub const TOKEN_DECIMALS: u8 = 6;
+
+/// Virtual SOL liquidity, in lamports, that `compute_reserves_for_market_cap` anchors its suggestion on
+#[constant]
+pub const DEFAULT_VIRTUAL_SOL_LIQUIDITY: u64 = 30_000_000_000u64;
+
+/// Longest cooldown, in seconds, the owner can put between two config changes
+#[constant]
+pub const MAX_CONFIG_CHANGE_COOLDOWN: i64 = 7 * 24 * 60 * 60;
+
+/// Fixed-point scale of the reserve ratio returned by `get_reserve_ratio`
+#[constant]
+pub const RESERVE_RATIO_SCALE: u64 = 1_000_000_000u64;
+
+/// Default tolerance, in basis points, for a fall in a curve's k across a single trade
+#[constant]
+pub const DEFAULT_K_TOLERANCE_BPS: u16 = 1;
+
+/// Curve type reported by `get_launch_info` for the constant product curve, the only one supported
+#[constant]
+pub const CURVE_TYPE_CONSTANT_PRODUCT: u8 = 0;
+
+/// Maximum number of curves `get_curves_summary` reports on, so its output fits in return data
+#[constant]
+pub const MAX_CURVES_PER_SUMMARY: u8 = 16;
+
+/// Number of wallets `collect_fees` can split the trade fees between
+#[constant]
+pub const MAX_FEE_RECIPIENTS: u8 = 4;
+
+/// Event verbosity emitting the full `TradeEvent` after every trade
+#[constant]
+pub const EVENT_VERBOSITY_FULL: u8 = 0;
+
+/// Event verbosity emitting only the compact `TradeEventMinimal` after every trade
+#[constant]
+pub const EVENT_VERBOSITY_MINIMAL: u8 = 1;
@@ -0,0 +1,170 @@
+use anchor_lang::prelude::*;
+
+#[error_code]
+pub enum MiniPumpError {
+    #[msg("Insufficient token balance")]
+    InsufficientTokenBalance,
+    #[msg("Insufficient SOL balance")]
+    InsufficientSolBalance,
+    #[msg("Arithmetic overflow")]
+    ArithmeticOverflow,
+    #[msg("Invalid token amount")]
+    InvalidTokenAmount,
+    #[msg("Invalid SOL amount")]
+    InvalidSolAmount,
+    #[msg("Calculation error")]
+    CalculationError,
+    #[msg("Token sold limit reached")]
+    TokenSoldLimitReached,
+    #[msg("Bonding curve not active")]
+    BondingCurveNotActive,
+
+    /// Returned when someone other than the protocol owner calls an owner-only instruction
+    #[msg("Not owner")]
+    NotOwner,
+
+    /// Returned when attempting to migrate from an active bonding curve
+    /// Migration must only occur after the bonding curve phase is complete
+    #[msg("Bonding curve is active")]
+    BondingCurveActive,
+
+    /// Returned when a buy purchases fewer tokens than the configured floor
+    #[msg("Buy is below the minimum token amount")]
+    BuyTooSmall,
+
+    /// Returned when a launch reuses a symbol already claimed in the registry
+    #[msg("Symbol is already taken")]
+    SymbolTaken,
+
+    /// Returned when symbol uniqueness is enforced but no registry account was passed
+    #[msg("Symbol registry account is required")]
+    MissingSymbolRegistry,
+
+    /// Returned when the metadata uri exceeds the configured maximum length
+    #[msg("Metadata uri is too long")]
+    UriTooLong,
+
+    /// Returned when the basket's remaining accounts do not match its token amounts
+    #[msg("Invalid basket accounts")]
+    InvalidBasketAccounts,
+
+    /// Returned when the buyer token account does not hold the traded mint or belong to the buyer
+    #[msg("Invalid buyer token account")]
+    InvalidBuyerTokenAccount,
+
+    /// Returned when graduating a curve that has not sold its full allocation
+    #[msg("Token sell limit not reached")]
+    SellLimitNotReached,
+
+    /// Returned when a launch would mint more than the configured per-launch supply ceiling
+    #[msg("Launch supply exceeds the maximum")]
+    SupplyTooLarge,
+
+    /// Returned when a basis-point parameter exceeds 10_000 (100%)
+    #[msg("Basis points cannot exceed 10000")]
+    InvalidBasisPoints,
+
+    /// Returned when the sell penalty window is negative
+    #[msg("Invalid sell penalty window")]
+    InvalidSellPenaltyWindow,
+
+    /// Returned when the sell penalty is enabled but no trader position account was passed
+    #[msg("Trader position account is required")]
+    MissingTraderPosition,
+
+    /// Returned when a buy takes a larger share of the unsold tokens than the configured maximum
+    #[msg("Buy exceeds the maximum share of remaining tokens")]
+    BuyTooLarge,
+
+    /// Returned when a trade lets the curve's constant product fall beyond the configured tolerance
+    #[msg("Curve invariant violated")]
+    CurveInvariantViolated,
+
+    /// Returned when deactivating a curve whose escrow holds less than the graduation minimum
+    #[msg("Escrow is below the minimum graduation SOL")]
+    BelowGraduationMinimum,
+
+    /// Returned when trading on a curve that has graduated
+    #[msg("Bonding curve has graduated")]
+    CurveGraduated,
+
+    /// Returned when operating on a curve whose assets were already withdrawn for migration
+    #[msg("Bonding curve has been migrated")]
+    CurveMigrated,
+
+    /// Returned when a buy would push the spot price above the buyer's maximum
+    #[msg("Price exceeds the maximum")]
+    PriceTooHigh,
+
+    /// Returned when an owner config change comes before the configured cooldown has elapsed
+    #[msg("Config change is too soon after the previous one")]
+    ConfigChangeTooSoon,
+
+    /// Returned when a trade receives less than the caller's minimum
+    #[msg("Slippage tolerance exceeded")]
+    SlippageExceeded,
+
+    /// Returned when a buy expects a different config epoch than the curve launched under
+    #[msg("Curve config differs from the expected epoch")]
+    CurveConfigChanged,
+
+    /// Returned when the buyer holds fewer lamports beyond the purchase than the configured minimum
+    #[msg("Buyer does not meet the minimum balance")]
+    BuyerIneligible,
+
+    /// Returned when the event verbosity is not one of the `EVENT_VERBOSITY_*` levels
+    #[msg("Invalid event verbosity")]
+    InvalidEventVerbosity,
+
+    /// Returned when launching while the owner has paused new launches
+    #[msg("Launches are paused")]
+    LaunchesPaused,
+
+    /// Returned when withdrawing from a curve that never sold any tokens
+    #[msg("Nothing to migrate")]
+    NothingToMigrate,
+
+    /// Returned when a curves summary gets no curve accounts or more than `MAX_CURVES_PER_SUMMARY`
+    #[msg("Invalid curves summary accounts")]
+    InvalidSummaryAccounts,
+
+    /// Returned when a supply split sells nothing or leaves no migration reserve
+    #[msg("Supply split does not reconcile")]
+    SupplyReconciliationError,
+
+    /// Returned when launching while as many curves are trading as `max_active_curves` allows
+    #[msg("Too many active curves")]
+    TooManyActiveCurves,
+
+    /// Returned when launching with an empty name or symbol
+    #[msg("Token name and symbol must not be empty")]
+    EmptyMetadataField,
+
+    /// Returned when trading while the owner has paused the protocol
+    #[msg("Trading is paused")]
+    ProtocolPaused,
+
+    /// Returned when proposing the default key as the next owner
+    #[msg("New owner must not be the default key")]
+    InvalidNewOwner,
+
+    /// Returned when a key other than the proposed owner accepts ownership
+    #[msg("Signer is not the pending owner")]
+    NotPendingOwner,
+
+    /// Returned when the config change cooldown is negative or above the maximum
+    #[msg("Config change cooldown is out of range")]
+    InvalidCooldown,
+
+    /// Returned when fee recipients repeat a key, use the default key or have shares not summing to 10000
+    #[msg("Invalid fee split")]
+    InvalidFeeSplit,
+
+    /// Returned when the fee collection's remaining accounts do not match the fee recipients
+    #[msg("Invalid fee recipient accounts")]
+    InvalidFeeRecipientAccounts,
+
+    /// Returned when a launch would sell at least the curve's whole virtual token reserve
+    #[msg("Tokens to sell must be below the virtual token liquidity")]
+    SupplyExceedsLiquidity,
+}
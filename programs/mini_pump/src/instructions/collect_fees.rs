@@ -0,0 +1,94 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+
+use crate::errors::MiniPumpError;
+use crate::state::global_state::{FeeRecipient, GlobalState};
+
+/// # Collect Fees Instruction
+///
+/// Owner-only instruction collecting the trade fees accumulated in the fee vault PDA.
+/// The vault keeps its rent-exempt minimum so it can keep accepting fees smaller than
+/// the rent minimum.
+///
+/// ## Account Ordering
+/// Once the owner has set fee recipients, `remaining_accounts` lists each recipient's
+/// wallet (writable) in the order they were set, and the fees go to them instead of the
+/// owner.
+#[derive(Accounts)]
+pub struct CollectFees<'info> {
+    /// The protocol owner, receiving the collected fees while no recipients are set
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// The global state account recording the protocol owner
+    #[account(
+        seeds = ["global_state".as_bytes()],
+        bump = global_state.bump,
+    )]
+    pub global_state: Account<'info, GlobalState>,
+
+    /// The fee vault holding the trade fees
+    #[account(
+        mut,
+        seeds = ["fee_vault".as_bytes()],
+        bump,
+    )]
+    pub fee_vault: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CollectFees<'info> {
+    /// Moves `amount` lamports from the fee vault to the owner, or splits them between the
+    /// fee recipients
+    ///
+    /// Each recipient is paid its share rounded down and the last one also takes the
+    /// rounding remainder, so the whole amount leaves the vault. Fails if the collection
+    /// would take the vault below its rent-exempt minimum.
+    pub fn collect_fees(&mut self, remaining_accounts: &'info [AccountInfo<'info>], amount: u64, bumps: CollectFeesBumps) -> Result<()> {
+        require!(self.owner.key() == self.global_state.owner, MiniPumpError::NotOwner);
+        require!(amount > 0, MiniPumpError::InvalidSolAmount);
+
+        let available = self.fee_vault.lamports().saturating_sub(Rent::get()?.minimum_balance(0));
+        require!(amount <= available, MiniPumpError::InsufficientSolBalance);
+
+        let fee_vault_seeds = &["fee_vault".as_bytes(), &[bumps.fee_vault]];
+        let fee_vault_signer = &[&fee_vault_seeds[..]];
+
+        let fee_recipients: Vec<FeeRecipient> = self.global_state.active_fee_recipients().copied().collect();
+        if fee_recipients.is_empty() {
+            require!(remaining_accounts.is_empty(), MiniPumpError::InvalidFeeRecipientAccounts);
+
+            return self.pay_from_vault(self.owner.to_account_info(), amount, fee_vault_signer);
+        }
+
+        require!(remaining_accounts.len() == fee_recipients.len(), MiniPumpError::InvalidFeeRecipientAccounts);
+
+        let mut paid = 0u64;
+        for (index, (fee_recipient, wallet)) in fee_recipients.iter().zip(remaining_accounts).enumerate() {
+            require!(wallet.key() == fee_recipient.recipient && wallet.is_writable, MiniPumpError::InvalidFeeRecipientAccounts);
+
+            let share = if index == fee_recipients.len() - 1 {
+                amount - paid
+            } else {
+                (amount as u128 * fee_recipient.share_bps as u128 / 10_000) as u64
+            };
+            paid += share;
+
+            if share > 0 {
+                self.pay_from_vault(wallet.clone(), share, fee_vault_signer)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn pay_from_vault(&self, to: AccountInfo<'info>, amount: u64, fee_vault_signer: &[&[&[u8]]]) -> Result<()> {
+        let cpi_ctx = CpiContext::new_with_signer(self.system_program.to_account_info(), Transfer {
+            from: self.fee_vault.to_account_info(),
+            to,
+        }, fee_vault_signer);
+
+        transfer(cpi_ctx, amount)
+    }
+}
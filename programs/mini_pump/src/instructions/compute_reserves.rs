@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::DEFAULT_VIRTUAL_SOL_LIQUIDITY;
+use crate::errors::MiniPumpError;
+use crate::math::virtual_token_for_market_cap;
+
+/// # Compute Reserves Instruction
+///
+/// Read-only helper that translates a target starting market cap into the virtual
+/// reserves expected by `init_protocol`. Launchers reason in market-cap terms, so a
+/// frontend can call this (via simulation) before configuring the protocol.
+///
+/// No accounts are required; the suggestion is returned through return data.
+#[derive(Accounts)]
+pub struct ComputeReserves {}
+
+/// Virtual reserves suggested for a target starting market cap
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct SuggestedReserves {
+    /// Suggested virtual SOL liquidity in lamports
+    pub virtual_sol_liquidity: u64,
+    /// Suggested virtual token liquidity in token base units
+    pub virtual_token_liquidity: u64,
+}
+
+impl ComputeReserves {
+    /// Suggests virtual reserves whose starting price gives the requested market cap
+    ///
+    /// The starting price of the curve is `virtual_sol_liquidity / virtual_token_liquidity`
+    /// lamports per token base unit, so only their ratio sets the market cap. The SOL side
+    /// is anchored at `DEFAULT_VIRTUAL_SOL_LIQUIDITY`, which sets how deep the curve is, and
+    /// the token side is solved for the target; see `math::virtual_token_for_market_cap`.
+    ///
+    /// ## Parameters
+    /// - `target_mcap`: Desired starting market cap in lamports
+    /// - `supply`: Total token supply in base units
+    ///
+    /// ## Returns
+    /// - `Result<SuggestedReserves>`: The suggested virtual reserves
+    pub fn compute_reserves_for_market_cap(&self, target_mcap: u64, supply: u64) -> Result<SuggestedReserves> {
+        require!(target_mcap > 0, MiniPumpError::InvalidSolAmount);
+        require!(supply > 0, MiniPumpError::InvalidTokenAmount);
+
+        Ok(SuggestedReserves {
+            virtual_sol_liquidity: DEFAULT_VIRTUAL_SOL_LIQUIDITY,
+            virtual_token_liquidity: virtual_token_for_market_cap(DEFAULT_VIRTUAL_SOL_LIQUIDITY, supply, target_mcap)?,
+        })
+    }
+}
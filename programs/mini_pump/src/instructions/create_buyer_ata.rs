@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::state::bonding_curve::BondingCurve;
+
+/// # Create Buyer ATA Instruction
+///
+/// Creates a trader's associated token account for a curve's mint in its own
+/// transaction. Trades require the account to exist, so the rent is paid here
+/// rather than inside `buy_token`. Calling it again for an existing account is a no-op.
+#[derive(Accounts)]
+pub struct CreateBuyerAta<'info> {
+    /// The trader paying rent for, and owning, the token account
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    /// The trader's token account for the curve's mint
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        associated_token::mint = token_mint,
+        associated_token::authority = buyer,
+    )]
+    pub buyer_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// The bonding curve, ensuring the mint was launched through this program
+    #[account(
+        seeds = ["bonding_curve".as_bytes(), token_mint.key().as_ref()],
+        bump = bonding_curve.bump,
+    )]
+    pub bonding_curve: Account<'info, BondingCurve>,
+
+    /// The mint traded on the bonding curve
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,71 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::errors::MiniPumpError;
+use crate::state::bonding_curve::BondingCurve;
+use crate::state::global_state::GlobalState;
+
+/// # Deactivate Curve Instruction
+///
+/// Lets the protocol owner end a curve's trading phase before it reaches the sell
+/// limit, so its funds can be withdrawn and migrated. The escrow must hold at least
+/// the configured `min_graduation_sol`, which keeps curves too small to form a real
+/// pool from being graduated.
+#[derive(Accounts)]
+pub struct DeactivateCurve<'info> {
+    /// The protocol owner
+    pub owner: Signer<'info>,
+
+    /// The global state holding the owner and graduation minimum
+    #[account(
+        mut,
+        seeds = ["global_state".as_bytes()],
+        bump = global_state.bump,
+    )]
+    pub global_state: Account<'info, GlobalState>,
+
+    /// The bonding curve being deactivated
+    #[account(
+        mut,
+        seeds = ["bonding_curve".as_bytes(), token_mint.key().as_ref()],
+        bump = bonding_curve.bump,
+    )]
+    pub bonding_curve: Account<'info, BondingCurve>,
+
+    /// The SOL escrow holding the curve's proceeds
+    #[account(
+        seeds = ["bonding_curve_sol_escrow".as_bytes(), bonding_curve.key().as_ref()],
+        bump,
+    )]
+    pub sol_escrow: SystemAccount<'info>,
+
+    /// The mint traded on the bonding curve
+    pub token_mint: InterfaceAccount<'info, Mint>,
+}
+
+impl<'info> DeactivateCurve<'info> {
+    /// Stops trading on the curve once its escrow holds enough SOL to graduate
+    pub fn deactivate_curve(&mut self) -> Result<()> {
+        require!(self.owner.key() == self.global_state.owner, MiniPumpError::NotOwner);
+        require!(self.bonding_curve.is_active, MiniPumpError::BondingCurveNotActive);
+
+        // The rent-exempt minimum funded at launch is not trading proceeds
+        let escrow_sol = self.sol_escrow.lamports().saturating_sub(Rent::get()?.minimum_balance(0));
+        require!(escrow_sol >= self.global_state.min_graduation_sol, MiniPumpError::BelowGraduationMinimum);
+
+        end_trading(&mut self.global_state, &mut self.bonding_curve)
+    }
+}
+
+/// Ends an active curve's trading phase and frees its slot under `max_active_curves`
+///
+/// Every path that graduates a curve goes through here, so the active curve count is
+/// decremented exactly once per curve.
+pub fn end_trading(global_state: &mut GlobalState, bonding_curve: &mut BondingCurve) -> Result<()> {
+    require!(bonding_curve.is_active, MiniPumpError::BondingCurveNotActive);
+
+    bonding_curve.is_active = false;
+    global_state.active_curve_count = global_state.active_curve_count.checked_sub(1).ok_or(MiniPumpError::ArithmeticOverflow)?;
+
+    Ok(())
+}
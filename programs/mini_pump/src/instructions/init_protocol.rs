@@ -1,5 +1,7 @@
 use anchor_lang::prelude::*;
-use crate::state::global_state::GlobalState;
+use anchor_lang::system_program::{transfer, Transfer};
+use crate::constants::{DEFAULT_K_TOLERANCE_BPS, EVENT_VERBOSITY_FULL, MAX_FEE_RECIPIENTS};
+use crate::state::global_state::{FeeRecipient, GlobalState};
 #[derive(Accounts)]
 pub struct InitProtocol<'info> {
     #[account(mut)]
@@ -12,12 +14,33 @@ pub struct InitProtocol<'info> {
     bump,
     )]
     pub global_state: Account<'info, GlobalState>,
+
+    // protocol treasury collecting launch fees, funded to rent exemption here
+    #[account(
+        mut,
+        seeds = ["treasury".as_bytes()],
+        bump,
+    )]
+    pub treasury: SystemAccount<'info>,
+
+    // vault collecting the trade fee, funded to rent exemption here
+    #[account(
+        mut,
+        seeds = ["fee_vault".as_bytes()],
+        bump,
+    )]
+    pub fee_vault: SystemAccount<'info>,
     pub system_program: Program<'info, System>,
 }
 
 
 impl<'info> InitProtocol<'info> {
-    pub fn init_protocol(&mut self, total_tokens_to_mint: u64, virtual_sol_liquidity: u64, virtual_token_liquidity: u64, tokens_to_sell: Pubkey, bumps: InitProtocolBumps) -> Result<()> {
+    // total_tokens_to_mint and tokens_to_sell are in base units, the rest of the supply is
+    // kept for migration. fee_basis_points is the trade fee, below 10_000
+    pub fn init_protocol(&mut self, total_tokens_to_mint: u64, virtual_sol_liquidity: u64, virtual_token_liquidity: u64, tokens_to_sell: u64, fee_basis_points: u16, bumps: InitProtocolBumps) -> Result<()> {
+        GlobalState::validate_supply_split(total_tokens_to_mint, tokens_to_sell, virtual_token_liquidity)?;
+        GlobalState::validate_fee_basis_points(fee_basis_points)?;
+
         // set inner
         self.global_state.set_inner(GlobalState {
             owner: self.payer.key(),
@@ -25,8 +48,49 @@ impl<'info> InitProtocol<'info> {
             total_tokens_to_mint,
             virtual_sol_liquidity,
             virtual_token_liquidity,
+            min_tokens_per_buy: 0,
+            enforce_unique_symbols: false,
+            launch_fee: 0,
+            max_uri_len: 0,
+            graduation_bonus_sol: 0,
+            whale_threshold_sol: 0,
+            max_total_supply_per_launch: 0,
+            min_virtual_token_liquidity: 0,
+            sell_penalty_bps: 0,
+            sell_penalty_window: 0,
+            max_buy_fraction_bps: 0,
+            k_tolerance_bps: DEFAULT_K_TOLERANCE_BPS,
+            launch_count: 0,
+            min_graduation_sol: 0,
+            track_entry_prices: false,
+            liquidity_growth_bps: 0,
+            config_change_cooldown: 0,
+            last_config_change: 0,
+            config_epoch: 0,
+            upgrade_authority_target: Pubkey::default(),
+            min_buyer_lamports: 0,
+            event_verbosity: EVENT_VERBOSITY_FULL,
+            launches_paused: false,
+            max_active_curves: 0,
+            active_curve_count: 0,
+            fee_basis_points,
+            is_paused: false,
+            pending_owner: Pubkey::default(),
+            fee_recipients: [FeeRecipient::default(); MAX_FEE_RECIPIENTS as usize],
+            fee_in_token: false,
             bump: bumps.global_state,
         });
+
+        // fund the treasury and fee vault so fee transfers smaller than the rent minimum can be accepted
+        let rent_exempt_minimum = Rent::get()?.minimum_balance(0);
+        for vault in [&self.treasury, &self.fee_vault] {
+            if vault.lamports() < rent_exempt_minimum {
+                transfer(CpiContext::new(self.system_program.to_account_info(), Transfer {
+                    from: self.payer.to_account_info(),
+                    to: vault.to_account_info(),
+                }), rent_exempt_minimum - vault.lamports())?;
+            }
+        }
         
         Ok(())
     }
@@ -0,0 +1,109 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{TokenAccount, TokenInterface},
+};
+
+use crate::instructions::launch_coin::*;
+use crate::instructions::trade_coin::{Slippage, TradeCoin, TradeCoinBumps};
+use crate::state::TraderPosition;
+
+/// # Launch And Buy Instruction
+///
+/// Launches a token and makes the creator's first buy in the same instruction, so no
+/// other buyer can trade on the curve in between. The buy runs through the same logic
+/// as `buy_token` and fails the whole launch if it receives fewer than `min_tokens_out`.
+#[derive(Accounts)]
+#[instruction(name: String, symbol: String)]
+pub struct LaunchAndBuy<'info> {
+    /// Every account `launch_coin` takes, with the payer as the creator and first buyer
+    pub launch: LaunchCoin<'info>,
+
+    /// The creator's token account receiving the first buy
+    /// Created here since the mint does not exist before the launch
+    #[account(
+        init_if_needed,
+        payer = launch.payer,
+        associated_token::mint = launch.token_mint,
+        associated_token::authority = launch.payer,
+        associated_token::token_program = token_program,
+    )]
+    pub creator_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// The creator's position on the new curve
+    /// Only required while the sell penalty or entry price tracking is enabled
+    #[account(
+        init_if_needed,
+        payer = launch.payer,
+        space = 8 + TraderPosition::INIT_SPACE,
+        seeds = ["trader_position".as_bytes(), launch.bonding_curve.key().as_ref(), launch.payer.key().as_ref()],
+        bump,
+    )]
+    pub trader_position: Option<Account<'info, TraderPosition>>,
+
+    /// The vault collecting the first buy's trade fee
+    #[account(
+        mut,
+        seeds = ["fee_vault".as_bytes()],
+        bump,
+    )]
+    pub fee_vault: SystemAccount<'info>,
+
+    /// The same programs as in `launch`, which the creator accounts' init needs at this level
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> LaunchAndBuy<'info> {
+    /// Launches the token, then buys `sol_amount` lamports of it for the creator
+    ///
+    /// ## Parameters
+    /// - `name`, `symbol`, `uri`: Passed through to `launch_coin`
+    /// - `sol_amount`: SOL the creator spends on the first buy
+    /// - `min_tokens_out`: Fewest tokens the first buy may receive
+    /// - `bumps`: Bump seeds for PDAs used in the instruction
+    pub fn launch_and_buy(&mut self, name: String, symbol: String, uri: String, sol_amount: u64, min_tokens_out: u64, bumps: LaunchAndBuyBumps) -> Result<()> {
+        let LaunchAndBuyBumps { launch: launch_bumps, trader_position: trader_position_bump, fee_vault: fee_vault_bump } = bumps;
+        let LaunchCoinBumps { bonding_curve_sol_escrow: sol_escrow_bump, .. } = launch_bumps;
+
+        self.launch.launch_coin(name, symbol, uri, launch_bumps)?;
+
+        let launch = &self.launch;
+        let mut trade = TradeCoin {
+            buyer: launch.payer.clone(),
+            buyer_token_account: self.creator_token_account.clone(),
+            trader_position: self.trader_position.clone(),
+            sol_escrow: launch.bonding_curve_sol_escrow.clone(),
+            bonding_curve: launch.bonding_curve.clone(),
+            bonding_curve_token_account: launch.bonding_curve_token_account.clone(),
+            global_state: launch.global_state.clone(),
+            fee_vault: self.fee_vault.clone(),
+            token_mint: launch.token_mint.clone(),
+            token_program: self.token_program.clone(),
+            associated_token_program: self.associated_token_program.clone(),
+            system_program: self.system_program.clone(),
+        };
+        // The launch minted the supply after these were loaded
+        trade.bonding_curve_token_account.reload()?;
+        trade.token_mint.reload()?;
+        let trade_bumps = TradeCoinBumps {
+            trader_position: trader_position_bump,
+            sol_escrow: sol_escrow_bump,
+            fee_vault: fee_vault_bump,
+        };
+
+        trade.buy_token(sol_amount, 0, None, Some(Slippage::MinOut(min_tokens_out)), trade_bumps)?;
+        trade.exit(&crate::ID)?;
+
+        // Pick up the buy's writes so this instruction's own exit does not overwrite them
+        self.launch.bonding_curve.reload()?;
+        self.launch.global_state.reload()?;
+        self.creator_token_account.reload()?;
+        if let Some(trader_position) = self.trader_position.as_mut() {
+            trader_position.reload()?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,4 +1,6 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+use anchor_lang::system_program::{transfer, Transfer};
 use anchor_spl::{
     associated_token::AssociatedToken,
     token_interface::{Mint, TokenInterface, TokenAccount, mint_to, MintTo},
@@ -8,11 +10,16 @@ use anchor_spl::{
         CreateMetadataAccountsV3, 
         Metadata as Metaplex,
         mpl_token_metadata::ID as METAPLEX_ID,
+        mpl_token_metadata::MAX_URI_LENGTH,
     },
 };
 
+use crate::constants::TOKEN_DECIMALS;
+use crate::errors::MiniPumpError;
+use crate::math::raw_token_amount;
 use crate::state::global_state::GlobalState;
 use crate::state::bonding_curve::BondingCurve;
+use crate::state::symbol_registry::SymbolRegistry;
 
 /// # LaunchCoin Instruction
 ///
@@ -26,6 +33,7 @@ use crate::state::bonding_curve::BondingCurve;
 /// - Price decreases as tokens are sold back
 /// - Virtual liquidity parameters control the initial price and curve steepness
 #[derive(Accounts)]
+#[instruction(name: String, symbol: String)]
 pub struct LaunchCoin<'info> {
     /// The account paying for the initialization costs
     /// This account must be a signer and will pay for all account creation fees
@@ -50,7 +58,9 @@ pub struct LaunchCoin<'info> {
 
     /// SOL escrow account that will hold SOL from token purchases
     /// This PDA is derived from "bonding_curve_sol_escrow" and the bonding curve address
+    /// Funded to the rent-exempt minimum at launch so it can never be reaped
     #[account(
+        mut,
         seeds = ["bonding_curve_sol_escrow".as_bytes(), bonding_curve.key().as_ref()],
         bump,
     )]
@@ -61,22 +71,55 @@ pub struct LaunchCoin<'info> {
     #[account(
         init,
         payer = payer,
-        mint::decimals = 6,
+        mint::decimals = TOKEN_DECIMALS,
         mint::authority = bonding_curve,
         mint::freeze_authority = bonding_curve,
     )]
     pub token_mint: InterfaceAccount<'info, Mint>,
 
+    /// Metaplex metadata account for the token mint
+    /// This PDA is derived by the metadata program from "metadata", its program id and the mint
+    /// It is always empty here, since the mint it is derived from is created in this instruction
+    /// CHECK: Address is validated by the seeds and the account is created by the metadata program
+    #[account(
+        mut,
+        seeds = ["metadata".as_bytes(), token_metadata_program.key().as_ref(), token_mint.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
+    pub metadata: UncheckedAccount<'info>,
+
     /// Token account owned by the bonding curve
     /// Will hold the initial token supply that will be sold through the bonding curve
+    /// Created here, since its mint is created in this instruction it can never already hold tokens
     #[account(
-        init_if_needed,
+        init,
         payer = payer,
         associated_token::mint = token_mint,
         associated_token::authority = bonding_curve,
     )]
     pub bonding_curve_token_account: InterfaceAccount<'info, TokenAccount>,
 
+    /// Registry entry claiming the token symbol
+    /// This PDA is derived from "symbol_registry" and the sha256 hash of the symbol
+    /// Only required when the global state enforces unique symbols
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + SymbolRegistry::INIT_SPACE,
+        seeds = ["symbol_registry".as_bytes(), hash(symbol.as_bytes()).as_ref()],
+        bump,
+    )]
+    pub symbol_registry: Option<Account<'info, SymbolRegistry>>,
+
+    /// Protocol treasury receiving the launch fee
+    #[account(
+        mut,
+        seeds = ["treasury".as_bytes()],
+        bump,
+    )]
+    pub treasury: SystemAccount<'info>,
+
     /// SPL Token program for token operations
     pub token_program: Interface<'info, TokenInterface>,
 
@@ -101,9 +144,11 @@ impl<'info> LaunchCoin<'info> {
     ///
     /// This function performs the complete token initialization process:
     /// 1. Creates token metadata with the provided name, symbol, and URI
-    /// 2. Mints the initial token supply to the bonding curve's token account
-    /// 3. Initializes the bonding curve with virtual liquidity parameters
-    /// 4. Emits a launch event with key token information
+    /// 2. Funds the SOL escrow to the rent-exempt minimum
+    /// 3. Charges the launch fee to the payer and sends it to the treasury
+    /// 4. Mints the initial token supply to the bonding curve's token account
+    /// 5. Initializes the bonding curve with virtual liquidity parameters
+    /// 6. Emits a launch event with key token information
     ///
     /// ## Parameters
     /// - `name`: The name of the token (e.g., "Mini Pump Token")
@@ -114,6 +159,38 @@ impl<'info> LaunchCoin<'info> {
     /// ## Returns
     /// - `Result<()>`: Success or error
     pub fn launch_coin(&mut self, name: String, symbol: String, uri: String, bumps: LaunchCoinBumps) -> Result<()> {
+        // Operators can stop new launches while existing curves keep trading
+        require!(!self.global_state.launches_paused, MiniPumpError::LaunchesPaused);
+
+        // Bound how many curves trade, and later wait for migration, at once
+        let max_active_curves = self.global_state.max_active_curves;
+        require!(max_active_curves == 0 || self.global_state.active_curve_count < max_active_curves, MiniPumpError::TooManyActiveCurves);
+
+        // Nameless tokens break wallet displays
+        require!(!name.is_empty() && !symbol.is_empty(), MiniPumpError::EmptyMetadataField);
+
+        // Enforce the launchpad's uri limit, which may be stricter than Metaplex's
+        let max_uri_len = match self.global_state.max_uri_len {
+            0 => MAX_URI_LENGTH,
+            max_uri_len => (max_uri_len as usize).min(MAX_URI_LENGTH),
+        };
+        require!(uri.len() <= max_uri_len, MiniPumpError::UriTooLong);
+
+        // Bound the supply of every launch on this launchpad
+        let total_supply = self.global_state.total_tokens_to_mint;
+        let tokens_to_sell = self.global_state.tokens_to_sell;
+        let migration_reserve = self.global_state.migration_reserve()?;
+        let max_total_supply = self.global_state.max_total_supply_per_launch;
+        require!(
+            max_total_supply == 0 || total_supply <= raw_token_amount(max_total_supply, self.token_mint.decimals)?,
+            MiniPumpError::SupplyTooLarge
+        );
+
+        // Claim the symbol before anything is created so duplicates fail cleanly
+        if self.global_state.enforce_unique_symbols {
+            self.claim_symbol(bumps.symbol_registry)?;
+        }
+
         // Create the token metadata structure with the provided information
         let token_data = DataV2 {
             name,
@@ -141,7 +218,7 @@ impl<'info> LaunchCoin<'info> {
         let metadata_ctx = CpiContext::new_with_signer(
             self.token_metadata_program.to_account_info(),
             CreateMetadataAccountsV3 {
-                metadata: self.token_mint.to_account_info(),
+                metadata: self.metadata.to_account_info(),
                 mint: self.token_mint.to_account_info(),
                 mint_authority: self.bonding_curve.to_account_info(),
                 update_authority: self.bonding_curve.to_account_info(),
@@ -159,8 +236,15 @@ impl<'info> LaunchCoin<'info> {
         // - collection_details: None (not part of a collection)
         create_metadata_accounts_v3(metadata_ctx, token_data, false, true, None)?;
 
-        // Mint the initial token supply to the bonding curve's token account
-        // This creates 1 billion tokens (with 6 decimals) that will be sold through the bonding curve
+        // Keep the escrow PDA alive even before the first buy
+        self.fund_escrow_rent()?;
+
+        // Charge the launch fee once every account rent has been paid
+        self.charge_launch_fee()?;
+
+        // Mint the configured supply to the bonding curve's token account
+        // `total_tokens_to_mint` is already in base units, so it is minted without decimal scaling
+        // The supply accounting assumes the curve account holds exactly what is minted here
         mint_to(CpiContext::new_with_signer(
             self.token_program.to_account_info(),
             MintTo {
@@ -173,10 +257,15 @@ impl<'info> LaunchCoin<'info> {
                 self.token_mint.key().as_ref(),
                 &[bumps.bonding_curve],
             ]],
-        ), 1_000_000_000_000_000)?; // 1 billion tokens with 6 decimals
+        ), total_supply)?;
         
         msg!("Launching coin");
         
+        // Number this launch from the protocol-wide counter
+        let launch_id = self.global_state.launch_count;
+        self.global_state.launch_count = launch_id.checked_add(1).ok_or(MiniPumpError::ArithmeticOverflow)?;
+        self.global_state.active_curve_count = self.global_state.active_curve_count.checked_add(1).ok_or(MiniPumpError::ArithmeticOverflow)?;
+
         // Initialize the bonding curve with parameters from the global state
         // This sets up the virtual liquidity values that determine the token's price curve
         self.bonding_curve.set_inner(BondingCurve {
@@ -190,16 +279,86 @@ impl<'info> LaunchCoin<'info> {
             token_mint: self.token_mint.key(),
             // Bonding curve is active and ready for trading
             is_active: true,
+            // Everything beyond the sell limit stays in the curve for migration
+            migration_reserve,
+            // The launcher is recorded as the curve's creator
+            creator: self.payer.key(),
+            // Launch time for clients and time-based features
+            created_at: Clock::get()?.unix_timestamp,
+            // Sequential launch number
+            launch_id,
+            // Nothing has been migrated yet
+            migrated: false,
+            // No trades yet
+            trade_count: 0,
+            total_volume_sol: 0,
+            total_sol_raised: 0,
+            last_trade_timestamp: 0,
+            // Lets buyers detect a curve launched under different defaults than they expect
+            config_epoch: self.global_state.config_epoch,
+            max_tokens_sold: 0,
+            // The supply split the curve launched with
+            total_supply,
+            tokens_to_sell,
             // Store the bump for future PDA derivation
             bump: bumps.bonding_curve,
         });
 
         // Emit an event to notify listeners about the token launch
-        self.emit_launch_event();
+        self.emit_launch_event(total_supply);
+
+        Ok(())
+    }
+
+    /// Tops the SOL escrow up to the rent-exempt minimum, paid by the launcher
+    ///
+    /// Trades only move lamports tracked by the virtual reserves, so this balance
+    /// stays in the escrow until the curve's funds are withdrawn.
+    fn fund_escrow_rent(&self) -> Result<()> {
+        let rent_exempt_minimum = Rent::get()?.minimum_balance(0);
+        let escrow_lamports = self.bonding_curve_sol_escrow.lamports();
+        if escrow_lamports >= rent_exempt_minimum {
+            return Ok(());
+        }
+
+        transfer(CpiContext::new(self.system_program.to_account_info(), Transfer {
+            from: self.payer.to_account_info(),
+            to: self.bonding_curve_sol_escrow.to_account_info(),
+        }), rent_exempt_minimum - escrow_lamports)
+    }
+
+    /// Transfers the configured launch fee from the payer to the treasury
+    ///
+    /// Account rents are already paid at this point, so the payer's remaining
+    /// balance must cover the full fee.
+    fn charge_launch_fee(&self) -> Result<()> {
+        let launch_fee = self.global_state.launch_fee;
+        if launch_fee == 0 {
+            return Ok(());
+        }
+
+        require!(self.payer.lamports() >= launch_fee, MiniPumpError::InsufficientSolBalance);
+
+        transfer(CpiContext::new(self.system_program.to_account_info(), Transfer {
+            from: self.payer.to_account_info(),
+            to: self.treasury.to_account_info(),
+        }), launch_fee)
+    }
+
+    /// Records the token mint in the symbol registry entry
+    ///
+    /// A populated entry means another launch already uses the symbol.
+    fn claim_symbol(&mut self, bump: Option<u8>) -> Result<()> {
+        let token_mint = self.token_mint.key();
+        let registry = self.symbol_registry.as_mut().ok_or(MiniPumpError::MissingSymbolRegistry)?;
+
+        require!(registry.token_mint == Pubkey::default(), MiniPumpError::SymbolTaken);
+
+        registry.token_mint = token_mint;
+        registry.bump = bump.ok_or(MiniPumpError::MissingSymbolRegistry)?;
 
         Ok(())
     }
-    
 }
 
 /// Event emitted when a new token is launched
@@ -227,7 +386,7 @@ impl<'info> LaunchCoin<'info> {
     ///
     /// This function creates and emits an event containing key information about the token launch,
     /// including the token mint address, bonding curve parameters, and timestamp.
-    pub fn emit_launch_event(&self) {
+    pub fn emit_launch_event(&self, total_tokens_minted: u64) {
         emit!(LaunchTokens {
             // Address of the token mint for tracking
             token_mint: self.token_mint.key(),
@@ -237,8 +396,8 @@ impl<'info> LaunchCoin<'info> {
             virtual_sol_liquidity: self.global_state.virtual_sol_liquidity,
             // Initial virtual token liquidity from global state
             virtual_token_liquidity: self.global_state.virtual_token_liquidity,
-            // Total tokens minted (1 billion with 6 decimals)
-            total_tokens_minted: 1_000_000_000_000_000, // Same as the amount minted
+            // Total tokens minted in base units
+            total_tokens_minted, // Same as the amount minted
             // Current blockchain timestamp
             timestamp: Clock::get().unwrap().unix_timestamp,
         });
@@ -1,8 +1,28 @@
+pub mod collect_fees;
+pub mod compute_reserves;
+pub mod create_buyer_ata;
+pub mod deactivate_curve;
 pub mod init_protocol;
+pub mod launch_and_buy;
 pub mod launch_coin;
+pub mod query_curve;
+pub mod recover_stray_sol;
+pub mod sell_basket;
 pub mod trade_coin;
+pub mod update_global_params;
 pub mod withdraw_funds;
+pub mod withdraw_treasury;
+pub use collect_fees::*;
+pub use compute_reserves::*;
+pub use create_buyer_ata::*;
+pub use deactivate_curve::*;
 pub use init_protocol::*;
+pub use launch_and_buy::*;
 pub use launch_coin::*;
+pub use query_curve::*;
+pub use recover_stray_sol::*;
+pub use sell_basket::*;
 pub use trade_coin::*;
-pub use withdraw_funds::*;
\ No newline at end of file
+pub use update_global_params::*;
+pub use withdraw_funds::*;
+pub use withdraw_treasury::*;
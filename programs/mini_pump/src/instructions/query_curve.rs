@@ -0,0 +1,716 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::keccak;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+use crate::constants::{CURVE_TYPE_CONSTANT_PRODUCT, MAX_CURVES_PER_SUMMARY, RESERVE_RATIO_SCALE};
+use crate::errors::MiniPumpError;
+use crate::math::{calculate_liquidity_growth, calculate_sell_penalty, calculate_sol_for_token, calculate_sol_for_token_out, calculate_tokens_for_sol_out, calculate_trade_fee, compound_fee_bps, constant_product, effective_sell_penalty_bps, gross_up_for_trade_fee, price_impact_bps, price_q64, price_rounded_to_tick, raw_token_amount, spread_bps};
+use crate::instructions::trade_coin::{quote_buy, BuyQuote};
+use crate::instructions::withdraw_funds::check_withdraw_preconditions;
+use crate::state::bonding_curve::BondingCurve;
+use crate::state::global_state::GlobalState;
+use crate::state::trader_position::TraderPosition;
+
+/// # Query Curve Instructions
+///
+/// Read-only views over a bonding curve. They never mutate state and return their
+/// result through return data, so clients can simulate them instead of replicating
+/// the on-chain math.
+#[derive(Accounts)]
+pub struct QueryCurve<'info> {
+    /// The global state holding the trading configuration
+    #[account(
+        seeds = ["global_state".as_bytes()],
+        bump = global_state.bump,
+    )]
+    pub global_state: Account<'info, GlobalState>,
+
+    /// The bonding curve being queried
+    #[account(
+        seeds = ["bonding_curve".as_bytes(), token_mint.key().as_ref()],
+        bump = bonding_curve.bump,
+    )]
+    pub bonding_curve: Account<'info, BondingCurve>,
+
+    /// The mint traded on the bonding curve
+    pub token_mint: InterfaceAccount<'info, Mint>,
+}
+
+/// Outcome of a buy once every limit and withheld share is applied
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct BuyEffective {
+    /// Tokens (in base units) the buyer receives
+    pub tokens_out: u64,
+    /// SOL taken from the buyer
+    pub sol_charged: u64,
+    /// SOL handed back to the buyer
+    pub sol_refunded: u64,
+    /// SOL withheld as liquidity growth and the trade fee, which buys no tokens
+    pub fee: u64,
+    /// Tokens (in base units) the curve keeps as the fee in `fee_in_token` mode
+    pub token_fee: u64,
+}
+
+/// Tokens making up a share of the circulating supply, and the SOL to buy them
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct OwnershipQuote {
+    /// Tokens (in base units) equal to the requested share of `tokens_sold`
+    pub token_amount: u64,
+    /// SOL a single buy needs to receive at least that many tokens
+    pub sol_required: u64,
+}
+
+/// Trade counters a curve has accumulated since launch
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct TradeStats {
+    /// Number of buys and sells
+    pub trade_count: u64,
+    /// SOL traded across all buys and sells
+    pub total_volume_sol: u64,
+    /// SOL paid in by buyers minus SOL paid out to sellers
+    pub total_sol_raised: u64,
+}
+
+/// How long ago a curve launched and last traded
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct CurveTiming {
+    /// Seconds since the launch
+    pub age_seconds: i64,
+    /// Seconds since the latest buy or sell, or since the launch before the first trade
+    pub seconds_since_last_trade: i64,
+}
+
+/// Launch parameters of a curve that never change after launch
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct LaunchInfo {
+    /// The mint traded on the curve
+    pub token_mint: Pubkey,
+    /// The wallet that launched the curve
+    pub creator: Pubkey,
+    /// Decimals of the token mint
+    pub decimals: u8,
+    /// Tokens (in base units) minted to the curve at launch
+    pub total_supply: u64,
+    /// Tokens (in base units) the curve sells before graduating
+    pub tokens_to_sell: u64,
+    /// Tokens (in base units) the curve keeps for migration liquidity
+    pub migration_reserve: u64,
+    /// Pricing curve, see `CURVE_TYPE_CONSTANT_PRODUCT`
+    pub curve_type: u8,
+    /// Unix timestamp of the launch
+    pub created_at: i64,
+    /// Sequential launch number across the protocol
+    pub launch_id: u64,
+}
+
+impl<'info> QueryCurve<'info> {
+    /// Returns the curve's immutable launch parameters
+    ///
+    /// Clients can cache the result and only poll the reserves, which change with every trade.
+    pub fn get_launch_info(&self) -> Result<LaunchInfo> {
+        let bonding_curve = &self.bonding_curve;
+
+        Ok(LaunchInfo {
+            token_mint: bonding_curve.token_mint,
+            creator: bonding_curve.creator,
+            decimals: self.token_mint.decimals,
+            total_supply: bonding_curve.total_supply,
+            tokens_to_sell: bonding_curve.tokens_to_sell,
+            migration_reserve: bonding_curve.migration_reserve,
+            curve_type: CURVE_TYPE_CONSTANT_PRODUCT,
+            created_at: bonding_curve.created_at,
+            launch_id: bonding_curve.launch_id,
+        })
+    }
+
+    /// Returns the curve's trade count and SOL volume since launch in one read
+    pub fn get_trade_stats(&self) -> Result<TradeStats> {
+        Ok(TradeStats {
+            trade_count: self.bonding_curve.trade_count,
+            total_volume_sol: self.bonding_curve.total_volume_sol,
+            total_sol_raised: self.bonding_curve.total_sol_raised,
+        })
+    }
+
+    /// Returns the curve's age and the time since its last trade, from the current clock
+    pub fn get_timing(&self) -> Result<CurveTiming> {
+        let now = Clock::get()?.unix_timestamp;
+        let bonding_curve = &self.bonding_curve;
+        let last_trade = match bonding_curve.last_trade_timestamp {
+            0 => bonding_curve.created_at,
+            last_trade_timestamp => last_trade_timestamp,
+        };
+
+        Ok(CurveTiming {
+            age_seconds: now.saturating_sub(bonding_curve.created_at),
+            seconds_since_last_trade: now.saturating_sub(last_trade),
+        })
+    }
+
+    /// Returns how many tokens (in base units) can still be bought before graduation
+    pub fn get_tokens_remaining(&self) -> Result<u64> {
+        Ok(self.bonding_curve.tokens_to_sell.saturating_sub(self.bonding_curve.tokens_sold))
+    }
+
+    /// Returns the highest number of tokens (in base units) the curve ever had sold
+    pub fn get_peak_supply(&self) -> Result<u64> {
+        Ok(self.bonding_curve.max_tokens_sold)
+    }
+
+    /// Returns how many whole tokens can still be bought before graduation, rounded down
+    ///
+    /// Zero once the curve is no longer active.
+    pub fn get_tokens_remaining_whole(&self) -> Result<u64> {
+        if !self.bonding_curve.is_active {
+            return Ok(0);
+        }
+
+        Ok(self.get_tokens_remaining()? / raw_token_amount(1, self.token_mint.decimals)?)
+    }
+
+    /// Returns the curve's constant product k from its current virtual reserves
+    ///
+    /// Trades round in the curve's favour, so k may drift upwards slightly but should
+    /// never decrease.
+    pub fn get_k(&self) -> Result<u128> {
+        Ok(constant_product(self.bonding_curve.virtual_sol_liquidity, self.bonding_curve.virtual_token_liquidity))
+    }
+
+    /// Returns the keccak-256 hash of the curve's serialized fields
+    ///
+    /// Any trade or state change alters the hash, so light clients can poll this instead of
+    /// fetching and comparing the whole account.
+    pub fn get_state_hash(&self) -> Result<[u8; 32]> {
+        let bonding_curve: &BondingCurve = &self.bonding_curve;
+
+        Ok(keccak::hash(&bonding_curve.try_to_vec()?).to_bytes())
+    }
+
+    /// Returns the spot price in lamports per token base unit as a Q64.64 fixed-point number
+    ///
+    /// Divide by 2^64 to recover the price; see `math::price_q64`.
+    pub fn get_price_q64(&self) -> Result<u128> {
+        price_q64(self.bonding_curve.virtual_sol_liquidity, self.bonding_curve.virtual_token_liquidity)
+    }
+
+    /// Returns the spot price in lamports per whole token, rounded to the nearest `tick_size`
+    ///
+    /// A `tick_size` of zero skips the rounding; see `math::price_rounded_to_tick`.
+    pub fn get_price_rounded(&self, tick_size: u64) -> Result<u64> {
+        price_rounded_to_tick(self.bonding_curve.virtual_sol_liquidity, self.bonding_curve.virtual_token_liquidity, self.token_mint.decimals, tick_size)
+    }
+
+    /// Returns the smallest buy, in lamports, that receives at least one whole token
+    ///
+    /// Accounts for the trade fee and liquidity growth share but not for the buy limits,
+    /// which can still reject the trade.
+    pub fn min_sol_for_one_token(&self) -> Result<u64> {
+        let one_token = raw_token_amount(1, self.token_mint.decimals)?;
+
+        self.sol_for_token_out(one_token)
+    }
+
+    /// Returns the SOL a single buy needs to purchase every token left before graduation
+    ///
+    /// Prices the whole remaining supply along the curve, including the trade fee and
+    /// liquidity growth share, so buying this amount graduates the curve. The buy limits can still reject
+    /// such a buy. Zero once the curve is no longer active.
+    pub fn sol_to_graduate(&self) -> Result<u64> {
+        if !self.bonding_curve.is_active {
+            return Ok(0);
+        }
+
+        self.sol_for_token_out(self.get_tokens_remaining()?)
+    }
+
+    /// Returns the tokens equal to `bps` of the circulating supply, and the SOL to buy them
+    ///
+    /// Circulating supply is the curve's `tokens_sold`. The SOL accounts for the trade fee
+    /// and liquidity growth share, like `min_sol_for_one_token`.
+    pub fn tokens_for_ownership_bps(&self, bps: u16) -> Result<OwnershipQuote> {
+        require!(bps <= 10_000, MiniPumpError::InvalidBasisPoints);
+
+        let bonding_curve = &self.bonding_curve;
+        let token_amount = (bonding_curve.tokens_sold as u128 * bps as u128 / 10_000) as u64;
+        let sol_required = self.sol_for_token_out(token_amount)?;
+
+        Ok(OwnershipQuote { token_amount, sol_required })
+    }
+
+    /// Returns the smallest buy receiving at least `token_out` tokens, trade fee included
+    fn sol_for_token_out(&self, token_out: u64) -> Result<u64> {
+        let bonding_curve = &self.bonding_curve;
+        let (sol_fee_bps, token_fee_bps) = self.global_state.buy_fee_bps();
+        // In fee_in_token mode the curve also prices the tokens it keeps as the fee
+        let tokens_priced = gross_up_for_trade_fee(token_out, token_fee_bps)?;
+        let curve_sol = calculate_sol_for_token_out(bonding_curve.virtual_sol_liquidity, bonding_curve.virtual_token_liquidity, tokens_priced, self.global_state.liquidity_growth_bps)?;
+
+        gross_up_for_trade_fee(curve_sol, sol_fee_bps)
+    }
+
+    /// Returns the fully diluted valuation in lamports: the spot price times the minted supply
+    ///
+    /// fdv = virtual_sol_liquidity * mint_supply / virtual_token_liquidity, computed in u128
+    /// and rounded down. Unlike a market cap it counts the unsold and migration tokens too.
+    pub fn get_fdv(&self) -> Result<u64> {
+        let bonding_curve = &self.bonding_curve;
+        require!(bonding_curve.virtual_token_liquidity > 0, MiniPumpError::CalculationError);
+
+        let fdv = bonding_curve.virtual_sol_liquidity as u128 * self.token_mint.supply as u128 / bonding_curve.virtual_token_liquidity as u128;
+
+        u64::try_from(fdv).map_err(|_| error!(MiniPumpError::ArithmeticOverflow))
+    }
+
+    /// Returns the ratio of virtual SOL to virtual token reserves, scaled by `RESERVE_RATIO_SCALE`
+    ///
+    /// ratio = virtual_sol_liquidity * RESERVE_RATIO_SCALE / virtual_token_liquidity
+    ///
+    /// This is lamports per token base unit times 1e9, so a ratio of 27_958_993 means
+    /// 0.027958993 lamports per base unit. Buys raise it and sells lower it, and the size
+    /// of the move shows how deep the curve is.
+    pub fn get_reserve_ratio(&self) -> Result<u64> {
+        let bonding_curve = &self.bonding_curve;
+
+        let ratio = (bonding_curve.virtual_sol_liquidity as u128 * RESERVE_RATIO_SCALE as u128)
+            .checked_div(bonding_curve.virtual_token_liquidity as u128)
+            .ok_or(MiniPumpError::CalculationError)?;
+
+        u64::try_from(ratio).map_err(|_| error!(MiniPumpError::ArithmeticOverflow))
+    }
+
+    /// Quotes the SOL returned by buying with `sol_amount` and immediately selling everything
+    ///
+    /// The buy leg is priced by `quote_buy`, so it applies `buy_token`'s limits, clamp and
+    /// fee and fails wherever the buy would. The sell leg prices the purchased tokens against
+    /// the post-buy reserves. SOL a clamped buy is not charged is counted as returned. The gap between `sol_amount` and the result is
+    /// the effective round-trip spread.
+    ///
+    /// Both legs pay the trade fee and keep the liquidity growth share in the reserve as
+    /// trades do. The per-wallet sell penalty is not included; see `get_effective_fee` for it.
+    pub fn quote_round_trip(&self, sol_amount: u64) -> Result<u64> {
+        let bonding_curve = &self.bonding_curve;
+        let liquidity_growth_bps = self.global_state.liquidity_growth_bps;
+        let fee_basis_points = self.global_state.fee_basis_points;
+
+        // The buy leg is priced exactly as buy_token prices it, limits included
+        let BuyQuote { token_out, sol_charged, trade_fee, .. } = quote_buy(&self.global_state, bonding_curve, sol_amount)?;
+
+        let virtual_sol_liquidity = bonding_curve.virtual_sol_liquidity.checked_add(sol_charged - trade_fee).ok_or(MiniPumpError::ArithmeticOverflow)?;
+        let virtual_token_liquidity = bonding_curve.virtual_token_liquidity.checked_sub(token_out).ok_or(MiniPumpError::InsufficientTokenBalance)?;
+
+        let sol_out = calculate_sol_for_token(virtual_sol_liquidity, virtual_token_liquidity, token_out)?;
+        let sell_withheld = calculate_liquidity_growth(sol_out, liquidity_growth_bps)? + calculate_trade_fee(sol_out, fee_basis_points)?;
+        Ok(sol_out - sell_withheld + (sol_amount - sol_charged))
+    }
+
+    /// Previews a buy of `sol_amount` lamports exactly as `buy_token` would execute it
+    ///
+    /// Runs the same `trade_coin::quote_buy` as the trade, so the per-buy limits fail the
+    /// view with the same errors and the clamp at the sell limit is applied. A buy that
+    /// completes the curve is only charged for the tokens it receives, and the rest of
+    /// `sol_amount` is reported as refunded.
+    pub fn quote_buy_effective(&self, sol_amount: u64) -> Result<BuyEffective> {
+        require!(!self.bonding_curve.migrated, MiniPumpError::CurveMigrated);
+        require!(self.bonding_curve.is_active, MiniPumpError::CurveGraduated);
+
+        let quote = quote_buy(&self.global_state, &self.bonding_curve, sol_amount)?;
+
+        Ok(BuyEffective {
+            tokens_out: quote.token_out,
+            sol_charged: quote.sol_charged,
+            sol_refunded: sol_amount - quote.sol_charged,
+            fee: quote.liquidity_growth + quote.trade_fee,
+            token_fee: quote.token_fee,
+        })
+    }
+
+    /// Returns whether a buy of `sol_amount` lamports would sell the curve's last tokens and graduate it
+    ///
+    /// Runs the same `trade_coin::quote_buy` as `buy_token`, so a buy the per-buy limits
+    /// would reject fails the view with the same error.
+    pub fn would_graduate(&self, sol_amount: u64) -> Result<bool> {
+        require!(!self.bonding_curve.migrated, MiniPumpError::CurveMigrated);
+        require!(self.bonding_curve.is_active, MiniPumpError::CurveGraduated);
+
+        Ok(quote_buy(&self.global_state, &self.bonding_curve, sol_amount)?.completes_curve)
+    }
+
+    /// Quotes how far a trade would move the spot price, in basis points, without executing it
+    ///
+    /// `amount` is the SOL spent on a buy or the tokens sold on a sell. The post-trade
+    /// reserves mirror `buy_token` and `sell_token`, including the liquidity growth share.
+    /// A buy is priced by `quote_buy`, so it clamps at the sell limit and fails wherever
+    /// `buy_token` would.
+    pub fn quote_price_impact(&self, amount: u64, is_buy: bool) -> Result<u64> {
+        let bonding_curve = &self.bonding_curve;
+        let liquidity_growth_bps = self.global_state.liquidity_growth_bps;
+        let (virtual_sol_liquidity, virtual_token_liquidity) = (bonding_curve.virtual_sol_liquidity, bonding_curve.virtual_token_liquidity);
+
+        let (virtual_sol_after, virtual_token_after) = if is_buy {
+            // The trade fee never reaches the reserves
+            let BuyQuote { token_out, sol_charged, trade_fee, .. } = quote_buy(&self.global_state, bonding_curve, amount)?;
+            (
+                virtual_sol_liquidity.checked_add(sol_charged - trade_fee).ok_or(MiniPumpError::ArithmeticOverflow)?,
+                virtual_token_liquidity.checked_sub(token_out).ok_or(MiniPumpError::InsufficientTokenBalance)?,
+            )
+        } else {
+            let sol_out = calculate_sol_for_token(virtual_sol_liquidity, virtual_token_liquidity, amount)?;
+            let growth = calculate_liquidity_growth(sol_out, liquidity_growth_bps)?;
+            (
+                virtual_sol_liquidity.checked_sub(sol_out - growth).ok_or(MiniPumpError::InsufficientSolBalance)?,
+                virtual_token_liquidity.checked_add(amount).ok_or(MiniPumpError::ArithmeticOverflow)?,
+            )
+        };
+
+        price_impact_bps(virtual_sol_liquidity, virtual_token_liquidity, virtual_sol_after, virtual_token_after)
+    }
+}
+
+/// Read-only view summarizing several bonding curves in a single call
+///
+/// ## Account Ordering
+/// `remaining_accounts` lists the bonding curve accounts to summarize, between one and
+/// `MAX_CURVES_PER_SUMMARY` of them. The result holds one `CurveSummary` per account, in
+/// the same order, and repeats are summarized again.
+#[derive(Accounts)]
+pub struct QueryCurves {}
+
+/// Key stats of one bonding curve, as reported by `get_curves_summary`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct CurveSummary {
+    /// The mint traded on the curve
+    pub token_mint: Pubkey,
+    /// Spot price in lamports per token base unit, as a Q64.64 fixed-point number
+    pub price_q64: u128,
+    /// Tokens (in base units) sold through the curve
+    pub tokens_sold: u64,
+    /// Whether the curve is still trading
+    pub is_active: bool,
+}
+
+impl QueryCurves {
+    /// Returns a `CurveSummary` for each bonding curve in `remaining_accounts`
+    ///
+    /// Every account must be a bonding curve owned by this program.
+    ///
+    /// ## Parameters
+    /// - `remaining_accounts`: The bonding curves described in the instruction docs
+    pub fn get_curves_summary<'info>(&self, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<Vec<CurveSummary>> {
+        require!(
+            !remaining_accounts.is_empty() && remaining_accounts.len() <= MAX_CURVES_PER_SUMMARY as usize,
+            MiniPumpError::InvalidSummaryAccounts
+        );
+
+        remaining_accounts
+            .iter()
+            .map(|info| {
+                let bonding_curve = Account::<BondingCurve>::try_from(info)?;
+
+                Ok(CurveSummary {
+                    token_mint: bonding_curve.token_mint,
+                    price_q64: price_q64(bonding_curve.virtual_sol_liquidity, bonding_curve.virtual_token_liquidity)?,
+                    tokens_sold: bonding_curve.tokens_sold,
+                    is_active: bonding_curve.is_active,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Read-only views over a curve's withdrawal and the assets it would migrate
+#[derive(Accounts)]
+pub struct QueryWithdraw<'info> {
+    /// The global state holding the graduation bonus
+    #[account(
+        seeds = ["global_state".as_bytes()],
+        bump = global_state.bump,
+    )]
+    pub global_state: Account<'info, GlobalState>,
+
+    /// The bonding curve being queried
+    #[account(
+        seeds = ["bonding_curve".as_bytes(), token_mint.key().as_ref()],
+        bump = bonding_curve.bump,
+    )]
+    pub bonding_curve: Account<'info, BondingCurve>,
+
+    /// The SOL escrow holding the curve's proceeds
+    #[account(
+        seeds = ["bonding_curve_sol_escrow".as_bytes(), bonding_curve.key().as_ref()],
+        bump,
+    )]
+    pub sol_escrow: SystemAccount<'info>,
+
+    /// The curve's token account holding the unsold tokens
+    #[account(
+        associated_token::mint = token_mint,
+        associated_token::authority = bonding_curve,
+    )]
+    pub bonding_curve_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// The protocol treasury funding the graduation bonus
+    #[account(
+        seeds = ["treasury".as_bytes()],
+        bump,
+    )]
+    pub treasury: SystemAccount<'info>,
+
+    /// The mint traded on the bonding curve
+    pub token_mint: InterfaceAccount<'info, Mint>,
+}
+
+/// Assets a curve would hand over for migration
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct MigrationAssets {
+    /// Lamports in the escrow above its rent-exempt minimum
+    pub sol_available: u64,
+    /// Tokens (in base units) held by the curve's token account
+    pub tokens_available: u64,
+}
+
+impl<'info> QueryWithdraw<'info> {
+    /// Returns whether every state precondition of `withdraw_funds` currently holds
+    ///
+    /// Only the caller's ownership is left out, since it depends on who signs.
+    pub fn is_withdrawable(&self) -> Result<bool> {
+        Ok(check_withdraw_preconditions(&self.global_state, &self.bonding_curve, &self.sol_escrow, &self.treasury).is_ok())
+    }
+
+    /// Returns the SOL and token legs available for migration in one read
+    pub fn get_migration_assets(&self) -> Result<MigrationAssets> {
+        Ok(MigrationAssets {
+            sol_available: self.sol_escrow.lamports().saturating_sub(Rent::get()?.minimum_balance(0)),
+            tokens_available: self.bonding_curve_token_account.amount,
+        })
+    }
+}
+
+/// Read-only view over the fee a trader would pay on a trade
+#[derive(Accounts)]
+pub struct QueryFee<'info> {
+    /// The global state holding the fee configuration
+    #[account(
+        seeds = ["global_state".as_bytes()],
+        bump = global_state.bump,
+    )]
+    pub global_state: Account<'info, GlobalState>,
+
+    /// The bonding curve being traded
+    #[account(
+        seeds = ["bonding_curve".as_bytes(), token_mint.key().as_ref()],
+        bump = bonding_curve.bump,
+    )]
+    pub bonding_curve: Account<'info, BondingCurve>,
+
+    /// The wallet the fee is quoted for
+    /// CHECK: Only used to derive the trader position address
+    pub trader: UncheckedAccount<'info>,
+
+    /// The trader's position on this curve, omitted if the wallet has never traded it
+    #[account(
+        seeds = ["trader_position".as_bytes(), bonding_curve.key().as_ref(), trader.key().as_ref()],
+        bump = trader_position.bump,
+    )]
+    pub trader_position: Option<Account<'info, TraderPosition>>,
+
+    /// The mint traded on the bonding curve
+    pub token_mint: InterfaceAccount<'info, Mint>,
+}
+
+/// Fee that would apply to a trade at the current time
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct EffectiveFee {
+    /// Fee rate in basis points of the trade's SOL leg
+    pub fee_bps: u16,
+    /// Fee in lamports
+    pub fee: u64,
+}
+
+impl<'info> QueryFee<'info> {
+    /// Quotes the fee the trader would pay on a trade of `amount` right now
+    ///
+    /// `amount` is SOL in for a buy and tokens in for a sell. Both directions pay the trade
+    /// fee and the liquidity growth share kept in the reserve, and a sell also pays the
+    /// decaying sell penalty, each computed exactly as the trade instructions do. A buy is
+    /// quoted by `quote_buy`, so its growth share compounds on what the trade fee leaves
+    /// and a buy `buy_token` would reject fails here too. A fee paid in tokens under
+    /// `fee_in_token` is valued at the SOL that priced those tokens.
+    pub fn get_effective_fee(&self, amount: u64, is_buy: bool) -> Result<EffectiveFee> {
+        let liquidity_growth_bps = self.global_state.liquidity_growth_bps;
+        if is_buy {
+            // The growth share is taken from what the trade fee leaves
+            let BuyQuote { sol_charged, trade_fee, liquidity_growth, .. } = quote_buy(&self.global_state, &self.bonding_curve, amount)?;
+            let (_, token_fee_bps) = self.global_state.buy_fee_bps();
+            let token_fee_value = calculate_trade_fee(sol_charged - trade_fee - liquidity_growth, token_fee_bps)?;
+            return Ok(EffectiveFee {
+                fee_bps: compound_fee_bps(self.global_state.fee_basis_points, liquidity_growth_bps),
+                fee: trade_fee + liquidity_growth + token_fee_value,
+            });
+        }
+
+        let sol_amount = calculate_sol_for_token(self.bonding_curve.virtual_sol_liquidity, self.bonding_curve.virtual_token_liquidity, amount)?;
+
+        sell_fee(&self.global_state, self.trader_position.as_deref(), sol_amount)
+    }
+
+    /// Quotes the spread between the effective buy and sell price right now, in basis points
+    ///
+    /// Both sides are priced off the same reserves, so the spread comes from the fees
+    /// alone: the trade fee and liquidity growth share on each side, plus the trader's
+    /// sell penalty if a position is passed.
+    pub fn get_spread_bps(&self) -> Result<u64> {
+        let global_state = &self.global_state;
+        // The fee rates do not depend on the trade size
+        let sell_fee_bps = sell_fee(global_state, self.trader_position.as_deref(), 0)?.fee_bps;
+
+        Ok(spread_bps(global_state.fee_basis_points, global_state.liquidity_growth_bps, sell_fee_bps))
+    }
+}
+
+/// Computes the fee withheld from a sell whose SOL leg is `sol_amount`, exactly as `sell_token` does
+///
+/// The fee is the trade fee, the liquidity growth share and the decaying sell penalty,
+/// which only applies to a trader with a recorded buy.
+fn sell_fee(global_state: &GlobalState, trader_position: Option<&TraderPosition>, sol_amount: u64) -> Result<EffectiveFee> {
+    let liquidity_growth_bps = global_state.liquidity_growth_bps;
+    let fee_basis_points = global_state.fee_basis_points;
+    let withheld = calculate_liquidity_growth(sol_amount, liquidity_growth_bps)? + calculate_trade_fee(sol_amount, fee_basis_points)?;
+
+    let last_buy_at = trader_position.map_or(0, |position| position.last_buy_at);
+    let (penalty_bps, sell_penalty) = if last_buy_at == 0 {
+        (0, 0)
+    } else {
+        let elapsed = Clock::get()?.unix_timestamp.saturating_sub(last_buy_at);
+        let (penalty_bps, window) = (global_state.sell_penalty_bps, global_state.sell_penalty_window);
+        (effective_sell_penalty_bps(penalty_bps, window, elapsed), calculate_sell_penalty(sol_amount, penalty_bps, window, elapsed)?)
+    };
+
+    Ok(EffectiveFee {
+        fee_bps: liquidity_growth_bps.saturating_add(fee_basis_points).saturating_add(penalty_bps),
+        fee: withheld.checked_add(sell_penalty).ok_or(MiniPumpError::ArithmeticOverflow)?,
+    })
+}
+
+/// Read-only view over the proceeds of selling a wallet's whole balance
+#[derive(Accounts)]
+pub struct QuerySellAll<'info> {
+    /// The global state holding the fee configuration
+    #[account(
+        seeds = ["global_state".as_bytes()],
+        bump = global_state.bump,
+    )]
+    pub global_state: Account<'info, GlobalState>,
+
+    /// The bonding curve being traded
+    #[account(
+        seeds = ["bonding_curve".as_bytes(), token_mint.key().as_ref()],
+        bump = bonding_curve.bump,
+    )]
+    pub bonding_curve: Account<'info, BondingCurve>,
+
+    /// The wallet selling its balance
+    /// CHECK: Only used to derive the trader's accounts
+    pub trader: UncheckedAccount<'info>,
+
+    /// The trader's token account whose whole balance is quoted
+    #[account(
+        associated_token::mint = token_mint,
+        associated_token::authority = trader,
+    )]
+    pub trader_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// The trader's position on this curve, omitted if the wallet has never traded it
+    #[account(
+        seeds = ["trader_position".as_bytes(), bonding_curve.key().as_ref(), trader.key().as_ref()],
+        bump = trader_position.bump,
+    )]
+    pub trader_position: Option<Account<'info, TraderPosition>>,
+
+    /// The mint traded on the bonding curve
+    pub token_mint: InterfaceAccount<'info, Mint>,
+}
+
+impl<'info> QuerySellAll<'info> {
+    /// Returns the lamports `sell_token` would pay out for the trader's entire token balance right now
+    ///
+    /// The payout is net of the trade fee, liquidity growth share and sell penalty, as in
+    /// `get_effective_fee`.
+    pub fn quote_sell_all(&self) -> Result<u64> {
+        let sol_amount = calculate_sol_for_token(self.bonding_curve.virtual_sol_liquidity, self.bonding_curve.virtual_token_liquidity, self.trader_token_account.amount)?;
+        let fee = sell_fee(&self.global_state, self.trader_position.as_deref(), sol_amount)?.fee;
+
+        sol_amount.checked_sub(fee).ok_or(error!(MiniPumpError::CalculationError))
+    }
+}
+
+/// Read-only views over a trader's position on a curve
+#[derive(Accounts)]
+pub struct QueryPosition<'info> {
+    /// The global state holding the liquidity growth share
+    #[account(
+        seeds = ["global_state".as_bytes()],
+        bump = global_state.bump,
+    )]
+    pub global_state: Account<'info, GlobalState>,
+
+    /// The bonding curve the position is on
+    #[account(
+        seeds = ["bonding_curve".as_bytes(), token_mint.key().as_ref()],
+        bump = bonding_curve.bump,
+    )]
+    pub bonding_curve: Account<'info, BondingCurve>,
+
+    /// The wallet holding the position
+    /// CHECK: Only used to derive the trader position address
+    pub trader: UncheckedAccount<'info>,
+
+    /// The trader's position on this curve
+    #[account(
+        seeds = ["trader_position".as_bytes(), bonding_curve.key().as_ref(), trader.key().as_ref()],
+        bump = trader_position.bump,
+    )]
+    pub trader_position: Account<'info, TraderPosition>,
+
+    /// The mint traded on the bonding curve
+    pub token_mint: InterfaceAccount<'info, Mint>,
+}
+
+impl<'info> QueryPosition<'info> {
+    /// Returns the trader's average entry price as a Q64.64 number of lamports per token base unit
+    ///
+    /// Averages every buy made while entry tracking was enabled. Sells do not change it,
+    /// so it stays the cost basis of the tokens bought. Fails if no tracked buy exists.
+    pub fn get_avg_entry_price(&self) -> Result<u128> {
+        price_q64(self.trader_position.sol_spent, self.trader_position.tokens_bought)
+    }
+
+    /// Returns how many tokens the trader must sell at the current reserves to recover the SOL
+    /// spent on its tracked buys
+    ///
+    /// Accounts for the price falling across the sell and for the trade fee and liquidity
+    /// growth share, but not for the time-dependent sell penalty. `in_profit` is set when fewer tokens than the
+    /// trader bought already recover the cost.
+    pub fn get_break_even_sell_tokens(&self) -> Result<BreakEven> {
+        let tokens_to_sell = calculate_tokens_for_sol_out(
+            self.bonding_curve.virtual_sol_liquidity,
+            self.bonding_curve.virtual_token_liquidity,
+            self.trader_position.sol_spent,
+            // Both are shares of the sell's SOL leg, so they gross up together
+            self.global_state.liquidity_growth_bps.saturating_add(self.global_state.fee_basis_points),
+        )?;
+
+        Ok(BreakEven {
+            tokens_to_sell,
+            in_profit: tokens_to_sell < self.trader_position.tokens_bought,
+        })
+    }
+}
+
+/// Tokens a trader must sell to recover its cost
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct BreakEven {
+    /// Tokens (in base units) whose sale pays back the SOL spent
+    pub tokens_to_sell: u64,
+    /// Whether that is fewer tokens than the trader bought
+    pub in_profit: bool,
+}
@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::MiniPumpError;
+use crate::state::global_state::GlobalState;
+
+/// # Recover Stray SOL Instruction
+///
+/// Owner-only instruction returning SOL that was sent to the `global_state` PDA by
+/// mistake. Only lamports above the account's rent-exempt minimum are moved, so its
+/// data and rent exemption are untouched.
+#[derive(Accounts)]
+pub struct RecoverStraySol<'info> {
+    /// The protocol owner receiving the recovered SOL
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// The global state account holding the stray SOL
+    #[account(
+        mut,
+        seeds = ["global_state".as_bytes()],
+        bump = global_state.bump,
+    )]
+    pub global_state: Account<'info, GlobalState>,
+}
+
+impl<'info> RecoverStraySol<'info> {
+    /// Moves every lamport above the rent-exempt minimum from the global state to the owner
+    ///
+    /// The global state is owned by this program, so its lamports are debited directly
+    /// rather than through the system program.
+    pub fn recover_stray_sol(&mut self) -> Result<()> {
+        require!(self.owner.key() == self.global_state.owner, MiniPumpError::NotOwner);
+
+        let global_state = self.global_state.to_account_info();
+        let rent_exempt_minimum = Rent::get()?.minimum_balance(global_state.data_len());
+        let stray_lamports = global_state.lamports().saturating_sub(rent_exempt_minimum);
+        require!(stray_lamports > 0, MiniPumpError::InsufficientSolBalance);
+
+        global_state.sub_lamports(stray_lamports)?;
+        self.owner.add_lamports(stray_lamports)?;
+
+        Ok(())
+    }
+}
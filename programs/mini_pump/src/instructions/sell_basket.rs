@@ -0,0 +1,65 @@
+use std::collections::BTreeSet;
+
+use anchor_lang::prelude::*;
+
+use crate::errors::MiniPumpError;
+use crate::instructions::trade_coin::{TradeCoin, TradeCoinBumps};
+
+/// # Sell Basket Instruction
+///
+/// Sells positions on several bonding curves in a single transaction. Each sell runs
+/// through the same account validation and logic as `sell_token`, and any failing sell
+/// reverts the whole basket.
+///
+/// ## Account Ordering
+/// `remaining_accounts` holds one group per entry in `token_amounts`, in the same order.
+/// Each group lists the `TradeCoin` accounts exactly as `sell_token` expects them:
+/// 1. `buyer` (signer, writable) - must be the basket seller
+/// 2. `buyer_token_account` (writable)
+/// 3. `trader_position` (writable) - the program id when omitted
+/// 4. `sol_escrow` (writable)
+/// 5. `bonding_curve` (writable)
+/// 6. `bonding_curve_token_account` (writable)
+/// 7. `global_state` (writable)
+/// 8. `fee_vault` (writable)
+/// 9. `token_mint`
+/// 10. `token_program`
+/// 11. `associated_token_program`
+/// 12. `system_program`
+#[derive(Accounts)]
+pub struct SellBasket<'info> {
+    /// The wallet selling every position in the basket
+    #[account(mut)]
+    pub seller: Signer<'info>,
+}
+
+impl<'info> SellBasket<'info> {
+    /// Executes one sell per account group in `remaining_accounts`
+    ///
+    /// ## Parameters
+    /// - `remaining_accounts`: The account groups described in the instruction docs
+    /// - `token_amounts`: Tokens to sell on each curve, one entry per account group
+    /// - `min_sol_outs`: Fewest lamports accepted for each sell, as `min_sol_out` on
+    ///   `sell_token`, one entry per account group
+    pub fn sell_basket(&mut self, remaining_accounts: &'info [AccountInfo<'info>], token_amounts: Vec<u64>, min_sol_outs: Vec<u64>) -> Result<()> {
+        require!(!token_amounts.is_empty(), MiniPumpError::InvalidTokenAmount);
+        require!(min_sol_outs.len() == token_amounts.len(), MiniPumpError::InvalidTokenAmount);
+
+        let mut accounts = remaining_accounts;
+        for (token_amount, min_sol_out) in token_amounts.into_iter().zip(min_sol_outs) {
+            let mut bumps = TradeCoinBumps::default();
+            let mut trade = TradeCoin::try_accounts(&crate::ID, &mut accounts, &[], &mut bumps, &mut BTreeSet::new())?;
+
+            require!(trade.buyer.key() == self.seller.key(), MiniPumpError::InvalidBasketAccounts);
+
+            trade.sell_token(token_amount, min_sol_out, None, bumps)?;
+
+            // Persist the curve before the next group reads it, in case curves repeat
+            trade.exit(&crate::ID)?;
+        }
+
+        require!(accounts.is_empty(), MiniPumpError::InvalidBasketAccounts);
+
+        Ok(())
+    }
+}
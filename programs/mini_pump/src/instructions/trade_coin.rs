@@ -1,11 +1,16 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
-    associated_token::AssociatedToken, token::{transfer_checked, TransferChecked}, token_interface::{mint_to, Mint, MintTo, TokenAccount, TokenInterface}
+    associated_token::AssociatedToken, token::{transfer_checked, TransferChecked}, token_interface::{Mint, TokenAccount, TokenInterface}
 };
 use anchor_lang::system_program::{transfer, Transfer};
 
+use crate::constants::EVENT_VERBOSITY_MINIMAL;
+use crate::errors::MiniPumpError;
+use crate::instructions::deactivate_curve::end_trading;
+use crate::math::{assert_curve_invariants, calculate_liquidity_growth, calculate_sell_penalty, calculate_sol_for_token, calculate_sol_for_token_out, calculate_token_for_sol, calculate_trade_fee, check_escrow_covers, constant_product, gross_up_for_trade_fee, price_rounded_to_tick};
 use crate::state::BondingCurve;
 use crate::state::GlobalState;
+use crate::state::TraderPosition;
 
 
 #[derive(Accounts)]
@@ -13,15 +18,34 @@ pub struct TradeCoin<'info> {
     #[account(mut)]
     pub buyer: Signer<'info>,
 
+    /// The buyer's token account, which must already exist (see `create_buyer_ata`)
+    /// It must belong to the buyer and hold this curve's mint, so an account with
+    /// other parameters is rejected rather than reused
     #[account(
-        init_if_needed,
-        payer = buyer,
+        mut,
         associated_token::mint = token_mint,
         associated_token::authority = buyer,
+        constraint = buyer_token_account.mint == token_mint.key() @ MiniPumpError::InvalidBuyerTokenAccount,
+        constraint = buyer_token_account.owner == buyer.key() @ MiniPumpError::InvalidBuyerTokenAccount,
     )]
     pub buyer_token_account: InterfaceAccount<'info, TokenAccount>,
 
+    /// The buyer's position on this curve, recording when they last bought
+    /// Only required while the sell penalty is enabled
     #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + TraderPosition::INIT_SPACE,
+        seeds = ["trader_position".as_bytes(), bonding_curve.key().as_ref(), buyer.key().as_ref()],
+        bump,
+    )]
+    pub trader_position: Option<Account<'info, TraderPosition>>,
+
+    /// The curve's SOL escrow, paid into on buys and out of on sells
+    /// The seeds bind it to this bonding curve's canonical escrow PDA, so a trade can
+    /// never be redirected to another account
+    #[account(
+        mut,
         seeds = ["bonding_curve_sol_escrow".as_bytes(), bonding_curve.key().as_ref()],
         bump,
     )]
@@ -29,7 +53,7 @@ pub struct TradeCoin<'info> {
 
     #[account(
         mut,
-        seeds = ["bonding_curve".as_bytes(), bonding_curve.key().as_ref()],
+        seeds = ["bonding_curve".as_bytes(), token_mint.key().as_ref()],
         bump = bonding_curve.bump,
     )]
     pub bonding_curve: Account<'info, BondingCurve>,
@@ -48,6 +72,14 @@ pub struct TradeCoin<'info> {
     )]
     pub global_state: Account<'info, GlobalState>,
 
+    /// The vault collecting the trade fee on buys and sells
+    #[account(
+        mut,
+        seeds = ["fee_vault".as_bytes()],
+        bump,
+    )]
+    pub fee_vault: SystemAccount<'info>,
+
     pub token_mint: InterfaceAccount<'info, Mint>,
     pub token_program: Interface<'info, TokenInterface>,
 
@@ -58,12 +90,35 @@ pub struct TradeCoin<'info> {
 }
 
 impl<'info> TradeCoin<'info> {
-    pub fn buy_token(&mut self, sol_amount: u64,) -> Result<()> {
+    /// Buys tokens for up to `sol_amount` lamports
+    ///
+    /// A buy that would take more than the tokens left before the sell limit receives just
+    /// those tokens and is only charged the SOL they cost; the rest never leaves the buyer.
+    /// The trade fee goes to the fee vault and the remainder through the curve.
+    /// `max_price` caps the post-trade spot price in lamports per whole token, guarding
+    /// against unexpected curve states independently of the amount received. Zero disables it.
+    /// `expected_epoch`, when set, must match the config epoch the curve launched under.
+    /// `slippage`, when set, bounds the tokens received; see `Slippage`.
+    pub fn buy_token(&mut self, sol_amount: u64, max_price: u64, expected_epoch: Option<u64>, slippage: Option<Slippage>, bumps: TradeCoinBumps) -> Result<()> {
        
-        if !self.bonding_curve.is_active {
-            return Err(MiniPumpError::BondingCurveNotActive.into());
+        self.require_tradable()?;
+
+        if let Some(expected_epoch) = expected_epoch {
+            require!(self.bonding_curve.config_epoch == expected_epoch, MiniPumpError::CurveConfigChanged);
         }
 
+        let BuyQuote { token_out, sol_charged, trade_fee, completes_curve, .. } = quote_buy(&self.global_state, &self.bonding_curve, sol_amount)?;
+        let curve_sol = sol_charged - trade_fee;
+
+        // Checked before the SOL moves, so the buyer must hold the floor on top of the purchase
+        let min_buyer_lamports = self.global_state.min_buyer_lamports;
+        if min_buyer_lamports > 0 {
+            let required = sol_charged.checked_add(min_buyer_lamports).ok_or(MiniPumpError::ArithmeticOverflow)?;
+            require!(self.buyer.lamports() >= required, MiniPumpError::BuyerIneligible);
+        }
+
+        let k_before = constant_product(self.bonding_curve.virtual_sol_liquidity, self.bonding_curve.virtual_token_liquidity);
+
 
         let transfer_accounts = Transfer {
             from: self.buyer.to_account_info(),
@@ -72,37 +127,35 @@ impl<'info> TradeCoin<'info> {
 
         let transfer_ctx = CpiContext::new(self.system_program.to_account_info(), transfer_accounts);
 
-        transfer(transfer_ctx, sol_amount)?;
+        // Only the SOL the tokens cost moves, which is less than `sol_amount` at the sell limit
+        transfer(transfer_ctx, curve_sol)?;
+
+        if trade_fee > 0 {
+            let fee_ctx = CpiContext::new(self.system_program.to_account_info(), Transfer {
+                from: self.buyer.to_account_info(),
+                to: self.fee_vault.to_account_info(),
+            });
 
-        // sol received now trasnfer out the tokens 
-        // calculate the tokens to send out 
-        let mut token_out = self.calculate_token_for_sol(sol_amount)?;
+            transfer(fee_ctx, trade_fee)?;
+        }
+
+        if completes_curve {
+            end_trading(&mut self.global_state, &mut self.bonding_curve)?;
+        }
 
-      
+        let token_mint_key = self.token_mint.key();
 
         let bonding_curve: &mut Account<'info, BondingCurve> =  &mut self.bonding_curve;
 
-        // NOTE: This is actually a wrong approach! We need to calculate by the formula
-        // how much SOL they should give for the remaining token_out.
-        // 
-        // HOMEWORK for Turbine attendees: Figure out how to properly calculate this!
-        // The current implementation has a critical flaw - if only 1 token is left until
-        // the 800 million limit, it will take all the SOL amount and just give back 1 token,
-        // which is extremely unfair to the user.
-        //
-        // The correct approach would be to:
-        // 1. Check if we're hitting the limit
-        // 2. Calculate how much SOL is needed for the actual tokens being purchased
-        // 3. Refund the excess SOL to the buyer
-        if bonding_curve.tokens_sold + token_out > 800_000_000_000 {
-            token_out = 800_000_000_000 - bonding_curve.tokens_sold;
-            bonding_curve.is_active = false;
-        }
+        check_slippage(slippage, sol_charged, bonding_curve.virtual_sol_liquidity, bonding_curve.virtual_token_liquidity, token_out)?;
 
+        // Guard the migration liquidity against the real token balance, not just the tokens_sold counter
+        let remaining_balance = self.bonding_curve_token_account.amount.checked_sub(token_out).ok_or(MiniPumpError::InsufficientTokenBalance)?;
+        require!(remaining_balance >= bonding_curve.migration_reserve, MiniPumpError::InsufficientTokenBalance);
 
         let seeds = &[
             "bonding_curve".as_bytes(),
-            &bonding_curve.key().to_bytes(),
+            token_mint_key.as_ref(),
             &[bonding_curve.bump],
         ];
         
@@ -120,19 +173,66 @@ impl<'info> TradeCoin<'info> {
         transfer_checked(cpi_ctx, token_out , self.token_mint.decimals)?;
 
         bonding_curve.virtual_token_liquidity = bonding_curve.virtual_token_liquidity.checked_sub(token_out).ok_or(MiniPumpError::InsufficientTokenBalance)?;
-        bonding_curve.virtual_sol_liquidity = bonding_curve.virtual_sol_liquidity.checked_add(sol_amount).ok_or(MiniPumpError::ArithmeticOverflow)?;
+        bonding_curve.virtual_sol_liquidity = bonding_curve.virtual_sol_liquidity.checked_add(curve_sol).ok_or(MiniPumpError::ArithmeticOverflow)?;
         bonding_curve.tokens_sold = bonding_curve.tokens_sold.checked_add(token_out).ok_or(MiniPumpError::ArithmeticOverflow)?;
+        bonding_curve.max_tokens_sold = bonding_curve.max_tokens_sold.max(bonding_curve.tokens_sold);
+
+        assert_curve_invariants(k_before, constant_product(bonding_curve.virtual_sol_liquidity, bonding_curve.virtual_token_liquidity), self.global_state.k_tolerance_bps)?;
+
+        if max_price > 0 {
+            let price_after = price_rounded_to_tick(bonding_curve.virtual_sol_liquidity, bonding_curve.virtual_token_liquidity, self.token_mint.decimals, 0)?;
+            require!(price_after <= max_price, MiniPumpError::PriceTooHigh);
+        }
 
+        self.record_buy(sol_charged, token_out, bumps.trader_position)?;
+
+        self.record_trade_stats(true, sol_charged, curve_sol)?;
+
+        self.emit_whale_trade(true, sol_charged, token_out)?;
+
+        // Last, so the event carries the final post-trade state
+        self.emit_trade_event(true, sol_charged, token_out)?;
 
         Ok(())
     }
 
-    pub fn sell_token(&mut self, token_amount: u64,) -> Result<()> {
-        // now for selling first we transfer in the tokens from the caller. 
-        if !self.bonding_curve.is_active {
-            return Err(MiniPumpError::BondingCurveNotActive.into());
-        }
+    /// Sells `token_amount` tokens back to the curve
+    ///
+    /// `min_sol_out` is the fewest lamports the seller accepts, zero for no minimum.
+    /// `slippage`, when set, bounds the SOL paid out as well; see `Slippage`. Both bounds
+    /// are checked before either transfer, so a sell that fails them moves neither tokens
+    /// nor SOL.
+    pub fn sell_token(&mut self, token_amount: u64, min_sol_out: u64, slippage: Option<Slippage>, bumps: TradeCoinBumps) -> Result<()> {
+        self.require_tradable()?;
+
+        let k_before = constant_product(self.bonding_curve.virtual_sol_liquidity, self.bonding_curve.virtual_token_liquidity);
+
+        let sol_amount = calculate_sol_for_token(self.bonding_curve.virtual_sol_liquidity, self.bonding_curve.virtual_token_liquidity, token_amount)?;
+
+        // The growth share stays in the reserve, raising the curve's floor
+        let liquidity_growth = calculate_liquidity_growth(sol_amount, self.global_state.liquidity_growth_bps)?;
 
+        // The penalty stays in the escrow and joins the migration liquidity
+        let sell_penalty = self.sell_penalty(sol_amount)?;
+
+        // The trade fee leaves the escrow for the fee vault
+        let trade_fee = calculate_trade_fee(sol_amount, self.global_state.fee_basis_points)?;
+
+        let payout = sol_amount
+            .checked_sub(liquidity_growth)
+            .and_then(|amount| amount.checked_sub(sell_penalty))
+            .and_then(|amount| amount.checked_sub(trade_fee))
+            .ok_or(MiniPumpError::CalculationError)?;
+
+        require!(payout >= min_sol_out, MiniPumpError::SlippageExceeded);
+        check_slippage(slippage, token_amount, self.bonding_curve.virtual_token_liquidity, self.bonding_curve.virtual_sol_liquidity, payout)?;
+
+        // Make sure the escrow can pay out without dipping below rent exemption
+        // before any of the seller's tokens move
+        let escrow_outflow = payout.checked_add(trade_fee).ok_or(MiniPumpError::ArithmeticOverflow)?;
+        check_escrow_covers(self.sol_escrow.lamports(), Rent::get()?.minimum_balance(0), escrow_outflow)?;
+
+        // now transfer in the tokens from the caller
         let accounts = TransferChecked{
             from: self.buyer_token_account.to_account_info(),
             to: self.bonding_curve_token_account.to_account_info(),
@@ -144,8 +244,6 @@ impl<'info> TradeCoin<'info> {
 
         transfer_checked(cpi_ctx, token_amount, self.token_mint.decimals)?;
 
-        let sol_amount = self.calculate_sol_for_token(token_amount)?;
-
 
         let bonding_curve = &mut self.bonding_curve;
 
@@ -156,162 +254,335 @@ impl<'info> TradeCoin<'info> {
             to: self.buyer.to_account_info(),
         };
 
+        // The escrow PDA signs for its own lamports
+        let bonding_curve_key = bonding_curve.key();
         let seeds = &[
-            "bonding_curve".as_bytes(),
-            &bonding_curve.key().to_bytes(),
-            &[bonding_curve.bump],
+            "bonding_curve_sol_escrow".as_bytes(),
+            bonding_curve_key.as_ref(),
+            &[bumps.sol_escrow],
         ];
 
         let signer_seeds = &[&seeds[..]];
 
         let cpi_ctx = CpiContext::new_with_signer(self.system_program.to_account_info(), transfer_accounts, signer_seeds);
 
-        transfer(cpi_ctx, sol_amount)?;
+        transfer(cpi_ctx, payout)?;
+
+        if trade_fee > 0 {
+            let fee_ctx = CpiContext::new_with_signer(self.system_program.to_account_info(), Transfer {
+                from: self.sol_escrow.to_account_info(),
+                to: self.fee_vault.to_account_info(),
+            }, signer_seeds);
+
+            transfer(fee_ctx, trade_fee)?;
+        }
 
         bonding_curve.virtual_token_liquidity = bonding_curve.virtual_token_liquidity.checked_add(token_amount).ok_or(MiniPumpError::ArithmeticOverflow)?;
-        bonding_curve.virtual_sol_liquidity = bonding_curve.virtual_sol_liquidity.checked_sub(sol_amount).ok_or(MiniPumpError::InsufficientTokenBalance)?;
+        bonding_curve.virtual_sol_liquidity = bonding_curve.virtual_sol_liquidity.checked_sub(sol_amount - liquidity_growth).ok_or(MiniPumpError::InsufficientTokenBalance)?;
         bonding_curve.tokens_sold = bonding_curve.tokens_sold.checked_sub(token_amount).ok_or(MiniPumpError::ArithmeticOverflow)?;
 
-      
+        assert_curve_invariants(k_before, constant_product(bonding_curve.virtual_sol_liquidity, bonding_curve.virtual_token_liquidity), self.global_state.k_tolerance_bps)?;
+
+        self.record_trade_stats(false, sol_amount, escrow_outflow)?;
+
+        self.emit_whale_trade(false, sol_amount, token_amount)?;
+
+        // Last, so the event carries the final post-trade state
+        self.emit_trade_event(false, sol_amount, token_amount)?;
         
         Ok(())
     }
 
-    
+    /// Rejects trades while the protocol is paused, on a curve that has graduated, and on
+    /// one whose funds were migrated
+    fn require_tradable(&self) -> Result<()> {
+        require!(!self.global_state.is_paused, MiniPumpError::ProtocolPaused);
+        require!(!self.bonding_curve.migrated, MiniPumpError::CurveMigrated);
+        require!(self.bonding_curve.is_active, MiniPumpError::CurveGraduated);
+
+        Ok(())
+    }
+
+    /// Records the buy on the trader position while the sell penalty or entry tracking is enabled
+    fn record_buy(&mut self, sol_amount: u64, token_out: u64, bump: Option<u8>) -> Result<()> {
+        let track_entry_prices = self.global_state.track_entry_prices;
+        if !self.sell_penalty_enabled() && !track_entry_prices {
+            return Ok(());
+        }
+
+        let position = self.trader_position.as_mut().ok_or(MiniPumpError::MissingTraderPosition)?;
+        position.last_buy_at = Clock::get()?.unix_timestamp;
+        if track_entry_prices {
+            position.sol_spent = position.sol_spent.checked_add(sol_amount).ok_or(MiniPumpError::ArithmeticOverflow)?;
+            position.tokens_bought = position.tokens_bought.checked_add(token_out).ok_or(MiniPumpError::ArithmeticOverflow)?;
+        }
+        position.bump = bump.ok_or(MiniPumpError::MissingTraderPosition)?;
+
+        Ok(())
+    }
 
+    /// Updates the curve's trade counters and last trade time with a trade of `sol_amount` lamports before fees
+    /// and `sol_transferred` lamports moved into or out of the escrow
+    fn record_trade_stats(&mut self, is_buy: bool, sol_amount: u64, sol_transferred: u64) -> Result<()> {
+        let bonding_curve = &mut self.bonding_curve;
+        bonding_curve.trade_count = bonding_curve.trade_count.checked_add(1).ok_or(MiniPumpError::ArithmeticOverflow)?;
+        bonding_curve.total_volume_sol = bonding_curve.total_volume_sol.checked_add(sol_amount).ok_or(MiniPumpError::ArithmeticOverflow)?;
+        bonding_curve.last_trade_timestamp = Clock::get()?.unix_timestamp;
+        bonding_curve.total_sol_raised = if is_buy {
+            bonding_curve.total_sol_raised.checked_add(sol_transferred).ok_or(MiniPumpError::ArithmeticOverflow)?
+        } else {
+            // SOL sent straight to the escrow can let payouts exceed what trades raised
+            bonding_curve.total_sol_raised.saturating_sub(sol_transferred)
+        };
 
-    /// Calculates the amount of tokens to be received for a given SOL amount
-    /// 
-    /// This function implements a modified constant product formula for bonding curves:
-    /// 
-    /// The formula is derived from the constant product AMM formula: x * y = k
-    /// where x = virtual_sol_liquidity and y = virtual_token_liquidity
-    /// 
-    /// For a bonding curve with virtual liquidity, we use:
-    /// virtual_sol_liquidity * virtual_token_liquidity = constant (k)
-    /// 
-    /// When a user buys tokens with SOL:
-    /// (virtual_sol_liquidity + sol_amount) * new_token_supply = k
-    /// 
-    /// Since k = virtual_sol_liquidity * virtual_token_liquidity, we can substitute:
-    /// (virtual_sol_liquidity + sol_amount) * new_token_supply = virtual_sol_liquidity * virtual_token_liquidity
-    /// 
-    /// Solving for new_token_supply:
-    /// new_token_supply = (virtual_sol_liquidity * virtual_token_liquidity) / (virtual_sol_liquidity + sol_amount)
-    /// 
-    /// The tokens sent to the user are:
-    /// token_amount = virtual_token_liquidity - new_token_supply
-    /// 
-    /// This creates a price curve that increases as more tokens are purchased,
-    /// since the virtual_token_liquidity decreases with each purchase while
-    /// virtual_sol_liquidity increases, making each subsequent token more expensive.
-    /// 
-    /// Price curve visualization:
-    /// 
-    ///  Price
-    ///    ^
-    ///    |                                  /|
-    ///    |                                /
-    ///    |                              /
-    ///    |                            /
-    ///    |                         /
-    ///    |                      /
-    ///    |                   /
-    ///    |               /
-    ///    |          _/
-    ///    |____----
-    ///    +------------------------------------> Tokens Sold
+        Ok(())
+    }
+
+    /// Returns the SOL withheld from a sell based on the time since the seller's last buy
     ///
-    /// As more tokens are sold, the price increases exponentially due to the
-    /// constant product formula, creating a natural price discovery mechanism.
-    pub fn calculate_token_for_sol(&self, sol_amount: u64) -> Result<u64> {
-        let bonding_curve = &self.bonding_curve;
-        
-        // Calculate new token supply after adding SOL to the virtual liquidity
-        // Formula: new_token_supply = virtual_sol_liquidity * virtual_token_liquidity / (virtual_sol_liquidity + sol_amount)
-        let new_token_supply = bonding_curve.virtual_sol_liquidity * bonding_curve.virtual_token_liquidity / (bonding_curve.virtual_sol_liquidity + sol_amount);
-        
-        // The tokens to send out are the difference between current virtual token liquidity and new token supply
-        let token_amount = bonding_curve.virtual_token_liquidity - new_token_supply;
-        
-        Ok(token_amount)
+    /// Wallets without a recorded buy, such as ones that received tokens by transfer,
+    /// pay no penalty.
+    fn sell_penalty(&self, sol_amount: u64) -> Result<u64> {
+        if !self.sell_penalty_enabled() {
+            return Ok(0);
+        }
+
+        let position = self.trader_position.as_ref().ok_or(MiniPumpError::MissingTraderPosition)?;
+        if position.last_buy_at == 0 {
+            return Ok(0);
+        }
+
+        let elapsed = Clock::get()?.unix_timestamp.saturating_sub(position.last_buy_at);
+        calculate_sell_penalty(sol_amount, self.global_state.sell_penalty_bps, self.global_state.sell_penalty_window, elapsed)
+    }
+
+    fn sell_penalty_enabled(&self) -> bool {
+        self.global_state.sell_penalty_bps > 0 && self.global_state.sell_penalty_window > 0
+    }
+
+    /// Emits a WhaleTrade event when the trade moves more SOL than the configured threshold
+    fn emit_whale_trade(&self, is_buy: bool, sol_amount: u64, token_amount: u64) -> Result<()> {
+        let whale_threshold_sol = self.global_state.whale_threshold_sol;
+        if whale_threshold_sol == 0 || sol_amount <= whale_threshold_sol {
+            return Ok(());
+        }
+
+        emit!(WhaleTrade {
+            token_mint: self.token_mint.key(),
+            trader: self.buyer.key(),
+            is_buy,
+            sol_amount,
+            token_amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
     }
 
-    /// Calculates the amount of SOL to be received for a given token amount
-    /// 
-    /// This function implements the inverse of the modified constant product formula:
-    /// 
-    /// Starting with the constant product formula: x * y = k
-    /// where x = virtual_sol_liquidity and y = virtual_token_liquidity
-    /// 
-    /// When a user sells tokens:
-    /// new_sol_supply * (virtual_token_liquidity + token_amount) = k
-    /// 
-    /// Since k = virtual_sol_liquidity * virtual_token_liquidity, we can substitute:
-    /// new_sol_supply * (virtual_token_liquidity + token_amount) = virtual_sol_liquidity * virtual_token_liquidity
-    /// 
-    /// Solving for new_sol_supply:
-    /// new_sol_supply = (virtual_sol_liquidity * virtual_token_liquidity) / (virtual_token_liquidity + token_amount)
-    /// 
-    /// The SOL sent to the user is:
-    /// sol_amount = virtual_sol_liquidity - new_sol_supply
-    /// 
-    /// This creates a price curve that decreases as more tokens are sold,
-    /// since the virtual_token_liquidity increases with each sale while
-    /// virtual_sol_liquidity decreases, making each subsequent token less valuable.
-    /// 
-    /// Price curve for selling visualization:
-    /// 
-    ///  SOL Received
-    ///    ^
-    ///    |\
-    ///    | \
-    ///    |  \
-    ///    |   \
-    ///    |    \
-    ///    |     \
-    ///    |      \
-    ///    |       \
-    ///    |        \__
-    ///    |           ----___________
-    ///    +------------------------------------> Tokens Sold Back
+    /// Emits a `TradeEvent` with the curve's reserves as they stand after the trade
     ///
-    /// When selling tokens back to the curve, the amount of SOL received
-    /// decreases as more tokens are sold, following the inverse of the
-    /// bonding curve formula. This creates a natural disincentive for
-    /// large sell-offs and helps stabilize the token price.
-    pub fn calculate_sol_for_token(&self, token_amount: u64) -> Result<u64> {
+    /// Must only be called once every reserve and `tokens_sold` update has been applied,
+    /// so indexers can treat the emitted values as the canonical post-trade state. Under
+    /// minimal event verbosity a `TradeEventMinimal` is emitted instead.
+    fn emit_trade_event(&self, is_buy: bool, sol_amount: u64, token_amount: u64) -> Result<()> {
         let bonding_curve = &self.bonding_curve;
-        
-        // Calculate new SOL supply after adding tokens to the virtual liquidity
-        // Formula: new_sol_supply = virtual_sol_liquidity * virtual_token_liquidity / (virtual_token_liquidity + token_amount)
-        let new_sol_supply = bonding_curve.virtual_sol_liquidity * (bonding_curve.virtual_token_liquidity) / (bonding_curve.virtual_token_liquidity + token_amount);
-        
-        // The SOL to send out is the difference between current virtual SOL liquidity and new SOL supply
-        let sol_amount = bonding_curve.virtual_sol_liquidity - new_sol_supply;
-        
-        Ok(sol_amount)
+
+        if self.global_state.event_verbosity == EVENT_VERBOSITY_MINIMAL {
+            emit!(TradeEventMinimal {
+                sol_amount,
+                token_amount,
+                seq: bonding_curve.trade_count,
+            });
+
+            return Ok(());
+        }
+
+        emit!(TradeEvent {
+            token_mint: self.token_mint.key(),
+            trader: self.buyer.key(),
+            is_buy,
+            sol_amount,
+            token_amount,
+            virtual_sol_liquidity: bonding_curve.virtual_sol_liquidity,
+            virtual_token_liquidity: bonding_curve.virtual_token_liquidity,
+            tokens_sold: bonding_curve.tokens_sold,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
     }
 
 }
 
+/// What a buy receives once every per-buy limit is applied
+pub struct BuyQuote {
+    /// Tokens (in base units) sent to the buyer
+    pub token_out: u64,
+    /// SOL taken from the buyer including the trade fee, less than requested when the buy
+    /// is clamped at the sell limit
+    pub sol_charged: u64,
+    /// SOL sent to the fee vault instead of the curve
+    pub trade_fee: u64,
+    /// Tokens (in base units) the curve priced but keeps as the fee in `fee_in_token` mode
+    pub token_fee: u64,
+    /// SOL kept in the reserve without being priced against the curve
+    pub liquidity_growth: u64,
+    /// Whether the buy reaches the sell limit and graduates the curve
+    pub completes_curve: bool,
+}
+
+/// Computes what a buy of `sol_amount` lamports receives, enforcing the per-buy limits
+///
+/// Shared with the `quote_buy_effective` view so the preview can never disagree with the trade.
+pub fn quote_buy(global_state: &GlobalState, bonding_curve: &BondingCurve, sol_amount: u64) -> Result<BuyQuote> {
+    let liquidity_growth_bps = global_state.liquidity_growth_bps;
+    let (sol_fee_bps, token_fee_bps) = global_state.buy_fee_bps();
+
+    // The trade fee is split off first and never reaches the curve
+    let trade_fee = calculate_trade_fee(sol_amount, sol_fee_bps)?;
+    let curve_sol = sol_amount - trade_fee;
+
+    // Part of the SOL stays in the reserve without buying tokens, raising the curve's floor
+    let liquidity_growth = calculate_liquidity_growth(curve_sol, liquidity_growth_bps)?;
+
+    // calculate the tokens to send out, less those the curve keeps when the fee is paid in tokens
+    let tokens_priced = calculate_token_for_sol(bonding_curve.virtual_sol_liquidity, bonding_curve.virtual_token_liquidity, curve_sol - liquidity_growth)?;
+    let token_fee = calculate_trade_fee(tokens_priced, token_fee_bps)?;
+    let token_out = tokens_priced - token_fee;
+
+    // The floor is checked against the uncapped quote so the final buy can still complete the curve
+    require!(token_out >= global_state.min_tokens_per_buy, MiniPumpError::BuyTooSmall);
+
+    // Stop a single buy from taking most of the remaining supply and graduating the curve
+    let tokens_unsold = bonding_curve.tokens_to_sell.saturating_sub(bonding_curve.tokens_sold);
+    let completes_curve = token_out >= tokens_unsold;
+    let max_buy_fraction_bps = global_state.max_buy_fraction_bps;
+    if max_buy_fraction_bps > 0 {
+        let fraction_of = |tokens: u64| (tokens as u128 * max_buy_fraction_bps as u128 / 10_000) as u64;
+        let max_token_out = fraction_of(tokens_unsold);
+        // The buy finishing the curve only receives the unsold tail. It may take it once the tail
+        // fits the cap measured against the whole sale, or once no smaller buy clears the token
+        // floor, so a capped curve can still graduate instead of creeping towards the limit.
+        let takes_allowed_tail = completes_curve && (tokens_unsold <= fraction_of(bonding_curve.tokens_to_sell) || max_token_out < global_state.min_tokens_per_buy);
+        require!(token_out <= max_token_out || takes_allowed_tail, MiniPumpError::BuyTooLarge);
+    }
+
+    let (token_out, sol_charged, trade_fee, token_fee) = clamp_buy_at_sell_limit(global_state, bonding_curve, sol_amount, token_out, token_fee)?;
+    let liquidity_growth = if sol_charged == sol_amount {
+        liquidity_growth
+    } else {
+        calculate_liquidity_growth(sol_charged - trade_fee, liquidity_growth_bps)?
+    };
+
+    // Keep the virtual token reserve away from zero, where the price explodes
+    let virtual_token_after = bonding_curve.virtual_token_liquidity.checked_sub(token_out).ok_or(MiniPumpError::InsufficientTokenBalance)?;
+    require!(virtual_token_after >= global_state.min_virtual_token_liquidity, MiniPumpError::CalculationError);
+
+    Ok(BuyQuote { token_out, sol_charged, trade_fee, token_fee, liquidity_growth, completes_curve })
+}
+
+/// Clamps a buy of `sol_amount` lamports receiving `token_out` at the curve's sell limit
+///
+/// Returns the tokens the buy receives, the SOL it is charged, the trade fee included
+/// in that charge and the tokens the curve keeps as the fee in `fee_in_token` mode.
+/// Past the limit the buy receives only the unsold tokens and is charged the smallest
+/// amount buying them plus the fee on it, so a buyer arriving with one token left pays
+/// for one token instead of the whole order.
+pub fn clamp_buy_at_sell_limit(global_state: &GlobalState, bonding_curve: &BondingCurve, sol_amount: u64, token_out: u64, token_fee: u64) -> Result<(u64, u64, u64, u64)> {
+    let (sol_fee_bps, token_fee_bps) = global_state.buy_fee_bps();
+    let tokens_unsold = bonding_curve.tokens_to_sell.saturating_sub(bonding_curve.tokens_sold);
+    if token_out <= tokens_unsold {
+        return Ok((token_out, sol_amount, calculate_trade_fee(sol_amount, sol_fee_bps)?, token_fee));
+    }
+
+    // The curve prices the unsold tokens together with those it keeps as the fee
+    let tokens_priced = gross_up_for_trade_fee(tokens_unsold, token_fee_bps)?;
+    let curve_sol = calculate_sol_for_token_out(bonding_curve.virtual_sol_liquidity, bonding_curve.virtual_token_liquidity, tokens_priced, global_state.liquidity_growth_bps)?;
+    let sol_charged = gross_up_for_trade_fee(curve_sol, sol_fee_bps)?.min(sol_amount);
+
+    Ok((tokens_unsold, sol_charged, sol_charged.saturating_sub(curve_sol), tokens_priced - tokens_unsold))
+}
+
+/// The least output a trade accepts
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub enum Slippage {
+    /// An absolute minimum output, tokens on a buy or lamports on a sell
+    MinOut(u64),
+    /// A tolerance in basis points below the output at the pre-trade spot price
+    ///
+    /// Measured against the spot price rather than a quote, so price impact and any
+    /// share withheld from the trade all count toward the tolerance.
+    Bps(u16),
+}
+
+/// Fails with `SlippageExceeded` when `amount_out` falls short of what `slippage` accepts
+///
+/// `reserve_in` and `reserve_out` are the pre-trade virtual reserves on the input and
+/// output side, from which the spot output for `amount_in` is derived.
+fn check_slippage(slippage: Option<Slippage>, amount_in: u64, reserve_in: u64, reserve_out: u64, amount_out: u64) -> Result<()> {
+    let min_out = match slippage {
+        None => return Ok(()),
+        Some(Slippage::MinOut(min_out)) => min_out as u128,
+        Some(Slippage::Bps(bps)) => {
+            require!(bps <= 10_000, MiniPumpError::InvalidBasisPoints);
+            require!(reserve_in > 0, MiniPumpError::CalculationError);
+            let spot_out = amount_in as u128 * reserve_out as u128 / reserve_in as u128;
+            spot_out * (10_000 - bps as u128) / 10_000
+        }
+    };
+
+    require!(amount_out as u128 >= min_out, MiniPumpError::SlippageExceeded);
+
+    Ok(())
+}
+
+/// Event emitted after every trade with the curve's post-trade state
+#[event]
+pub struct TradeEvent {
+    /// The mint of the traded token
+    pub token_mint: Pubkey,
+    /// The wallet that made the trade
+    pub trader: Pubkey,
+    /// Whether the trade was a buy (false for a sell)
+    pub is_buy: bool,
+    /// SOL the trade was priced at, in lamports
+    pub sol_amount: u64,
+    /// Tokens bought or sold, in base units
+    pub token_amount: u64,
+    /// Virtual SOL liquidity after the trade
+    pub virtual_sol_liquidity: u64,
+    /// Virtual token liquidity after the trade
+    pub virtual_token_liquidity: u64,
+    /// Tokens sold on the curve after the trade
+    pub tokens_sold: u64,
+    /// Unix timestamp of the trade
+    pub timestamp: i64,
+}
+
+/// Compact event emitted after every trade under minimal event verbosity
+#[event]
+pub struct TradeEventMinimal {
+    /// SOL the trade was priced at, in lamports
+    pub sol_amount: u64,
+    /// Tokens bought or sold, in base units
+    pub token_amount: u64,
+    /// The curve's trade count after the trade, ordering its trades
+    pub seq: u64,
+}
 
-#[error_code]
-pub enum MiniPumpError {
-    #[msg("Insufficient token balance")]
-    InsufficientTokenBalance,
-    #[msg("Insufficient SOL balance")]
-    InsufficientSolBalance,
-    #[msg("Arithmetic overflow")]
-    ArithmeticOverflow,
-    #[msg("Invalid token amount")]
-    InvalidTokenAmount,
-    #[msg("Invalid SOL amount")]
-    InvalidSolAmount,
-    #[msg("Calculation error")]
-    CalculationError,
-    #[msg("Token sold limit reached")]
-    TokenSoldLimitReached,
-    #[msg("Bonding curve not active")]
-    BondingCurveNotActive,
+/// Event emitted when a single trade moves more SOL than the whale threshold
+#[event]
+pub struct WhaleTrade {
+    /// The mint of the traded token
+    pub token_mint: Pubkey,
+    /// The wallet that made the trade
+    pub trader: Pubkey,
+    /// Whether the trade was a buy (false for a sell)
+    pub is_buy: bool,
+    /// SOL paid in or out, in lamports
+    pub sol_amount: u64,
+    /// Tokens bought or sold, in base units
+    pub token_amount: u64,
+    /// Unix timestamp of the trade
+    pub timestamp: i64,
 }
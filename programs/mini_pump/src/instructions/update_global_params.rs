@@ -0,0 +1,561 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::{EVENT_VERBOSITY_MINIMAL, MAX_CONFIG_CHANGE_COOLDOWN, MAX_FEE_RECIPIENTS, TOKEN_DECIMALS};
+use crate::errors::MiniPumpError;
+use crate::math::raw_token_amount;
+use crate::state::global_state::{FeeRecipient, GlobalState};
+
+/// # Update Global Params Instruction
+///
+/// Owner-only instructions that adjust protocol-wide configuration stored in the
+/// global state after `init_protocol` has run. Each setter changes a single parameter
+/// so the owner can tune the protocol without redeploying.
+#[derive(Accounts)]
+pub struct UpdateGlobalParams<'info> {
+    /// The protocol owner authorized to change the configuration
+    pub owner: Signer<'info>,
+
+    /// The global state account holding the protocol configuration
+    #[account(
+        mut,
+        seeds = ["global_state".as_bytes()],
+        bump = global_state.bump,
+    )]
+    pub global_state: Account<'info, GlobalState>,
+}
+
+impl<'info> UpdateGlobalParams<'info> {
+    /// Sets the minimum number of tokens a single buy must purchase
+    ///
+    /// Expressing the floor in tokens rather than SOL keeps it meaningful as the
+    /// price rises along the curve. Zero disables the floor.
+    pub fn set_min_tokens_per_buy(&mut self, min_tokens_per_buy: u64) -> Result<()> {
+        self.only_owner()?;
+
+        self.global_state.min_tokens_per_buy = min_tokens_per_buy;
+
+        Ok(())
+    }
+
+    /// Toggles whether each launch must claim a unique symbol in the registry
+    pub fn set_enforce_unique_symbols(&mut self, enforce_unique_symbols: bool) -> Result<()> {
+        self.only_owner()?;
+
+        self.global_state.enforce_unique_symbols = enforce_unique_symbols;
+
+        Ok(())
+    }
+
+    /// Sets the SOL fee charged to the launcher on every launch
+    pub fn set_launch_fee(&mut self, launch_fee: u64) -> Result<()> {
+        self.only_owner()?;
+
+        self.global_state.launch_fee = launch_fee;
+
+        Ok(())
+    }
+
+    /// Sets the maximum metadata uri length accepted at launch
+    ///
+    /// Zero falls back to the Metaplex limit of 200 characters.
+    pub fn set_max_uri_len(&mut self, max_uri_len: u16) -> Result<()> {
+        self.only_owner()?;
+
+        self.global_state.max_uri_len = max_uri_len;
+
+        Ok(())
+    }
+
+    /// Sets the SOL bonus the treasury adds to the migration proceeds of each graduated curve
+    pub fn set_graduation_bonus_sol(&mut self, graduation_bonus_sol: u64) -> Result<()> {
+        self.only_owner()?;
+
+        self.global_state.graduation_bonus_sol = graduation_bonus_sol;
+
+        Ok(())
+    }
+
+    /// Sets the SOL size above which a trade also emits a WhaleTrade event
+    ///
+    /// Zero disables whale alerts.
+    pub fn set_whale_threshold_sol(&mut self, whale_threshold_sol: u64) -> Result<()> {
+        self.only_owner()?;
+
+        self.global_state.whale_threshold_sol = whale_threshold_sol;
+
+        Ok(())
+    }
+
+    /// Sets the maximum whole-token supply a single launch may mint
+    ///
+    /// Zero removes the ceiling.
+    pub fn set_max_total_supply_per_launch(&mut self, max_total_supply_per_launch: u64) -> Result<()> {
+        self.only_owner()?;
+
+        self.global_state.max_total_supply_per_launch = max_total_supply_per_launch;
+
+        Ok(())
+    }
+
+    /// Sets the floor a buy may not push a curve's virtual token liquidity below
+    ///
+    /// Bounds the tail of the curve where the price explodes. Zero disables the floor.
+    pub fn set_min_virtual_token_liquidity(&mut self, min_virtual_token_liquidity: u64) -> Result<()> {
+        self.only_owner()?;
+
+        self.global_state.min_virtual_token_liquidity = min_virtual_token_liquidity;
+
+        Ok(())
+    }
+
+    /// Configures the sell penalty that decays over time since a wallet's last buy
+    ///
+    /// A sell right after a buy has `sell_penalty_bps` of its proceeds withheld, falling
+    /// linearly to zero after `sell_penalty_window` seconds. A zero rate or window disables it.
+    pub fn set_sell_penalty(&mut self, sell_penalty_bps: u16, sell_penalty_window: i64) -> Result<()> {
+        self.only_owner()?;
+
+        require!(sell_penalty_bps <= 10_000, MiniPumpError::InvalidBasisPoints);
+        require!(sell_penalty_window >= 0, MiniPumpError::InvalidSellPenaltyWindow);
+
+        self.global_state.sell_penalty_bps = sell_penalty_bps;
+        self.global_state.sell_penalty_window = sell_penalty_window;
+
+        Ok(())
+    }
+
+    /// Sets the virtual reserves new curves start from
+    ///
+    /// Existing curves keep the reserves they launched with. Bumps the config epoch that
+    /// new curves record, and emits a `CurveDefaultsUpdated` event in the same instruction
+    /// so integrators can track the config history.
+    pub fn set_curve_defaults(&mut self, virtual_sol_liquidity: u64, virtual_token_liquidity: u64) -> Result<()> {
+        self.only_owner()?;
+
+        self.apply_curve_defaults(virtual_sol_liquidity, virtual_token_liquidity)
+    }
+
+    /// Sets the virtual reserves new curves start from together with the supply they mint
+    ///
+    /// Applies the reserves exactly as `set_curve_defaults` does, as a single config change.
+    /// `total_tokens_to_mint` is in base units and must exceed the current `tokens_to_sell`;
+    /// the sale amount is kept and the migration reserve takes the difference. The supply
+    /// is checked as in `set_launch_supply_split`. The owner is never changed here, only
+    /// through `transfer_ownership`.
+    pub fn update_global_params(&mut self, virtual_sol_liquidity: u64, virtual_token_liquidity: u64, total_tokens_to_mint: u64) -> Result<()> {
+        self.only_owner()?;
+
+        self.check_launch_supply(total_tokens_to_mint, self.global_state.tokens_to_sell, virtual_token_liquidity)?;
+        self.global_state.total_tokens_to_mint = total_tokens_to_mint;
+
+        self.apply_curve_defaults(virtual_sol_liquidity, virtual_token_liquidity)
+    }
+
+    fn apply_curve_defaults(&mut self, virtual_sol_liquidity: u64, virtual_token_liquidity: u64) -> Result<()> {
+        require!(virtual_sol_liquidity > 0, MiniPumpError::InvalidSolAmount);
+        require!(virtual_token_liquidity > 0, MiniPumpError::InvalidTokenAmount);
+
+        let old_vsol = self.global_state.virtual_sol_liquidity;
+        let old_vtoken = self.global_state.virtual_token_liquidity;
+        self.global_state.virtual_sol_liquidity = virtual_sol_liquidity;
+        self.global_state.virtual_token_liquidity = virtual_token_liquidity;
+        self.global_state.config_epoch = self.global_state.config_epoch.checked_add(1).ok_or(MiniPumpError::ArithmeticOverflow)?;
+
+        emit!(CurveDefaultsUpdated {
+            old_vsol,
+            old_vtoken,
+            new_vsol: virtual_sol_liquidity,
+            new_vtoken: virtual_token_liquidity,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Sets the largest share of a curve's unsold tokens a single buy may take, in basis points
+    ///
+    /// The buy finishing a curve may take the whole unsold tail once that tail fits the same
+    /// share of the curve's full sale, so capped curves still graduate. Zero disables the limit.
+    pub fn set_max_buy_fraction_bps(&mut self, max_buy_fraction_bps: u16) -> Result<()> {
+        self.only_owner()?;
+
+        require!(max_buy_fraction_bps <= 10_000, MiniPumpError::InvalidBasisPoints);
+
+        self.global_state.max_buy_fraction_bps = max_buy_fraction_bps;
+
+        Ok(())
+    }
+
+    /// Sets how far a trade may let a curve's constant product k fall, in basis points
+    pub fn set_k_tolerance_bps(&mut self, k_tolerance_bps: u16) -> Result<()> {
+        self.only_owner()?;
+
+        require!(k_tolerance_bps <= 10_000, MiniPumpError::InvalidBasisPoints);
+
+        self.global_state.k_tolerance_bps = k_tolerance_bps;
+
+        Ok(())
+    }
+
+    /// Sets the SOL the escrow must hold above rent before the owner can deactivate a curve manually
+    pub fn set_min_graduation_sol(&mut self, min_graduation_sol: u64) -> Result<()> {
+        self.only_owner()?;
+
+        self.global_state.min_graduation_sol = min_graduation_sol;
+
+        Ok(())
+    }
+
+    /// Toggles whether buys accumulate SOL spent and tokens bought on the trader position
+    ///
+    /// While enabled, buys must pass their trader position.
+    pub fn set_track_entry_prices(&mut self, track_entry_prices: bool) -> Result<()> {
+        self.only_owner()?;
+
+        self.global_state.track_entry_prices = track_entry_prices;
+
+        Ok(())
+    }
+
+    /// Sets the share of each trade's SOL kept in the virtual SOL reserve, in basis points
+    ///
+    /// See `math::calculate_liquidity_growth` for the cumulative effect. Zero disables it.
+    pub fn set_liquidity_growth_bps(&mut self, liquidity_growth_bps: u16) -> Result<()> {
+        self.only_owner()?;
+
+        require!(liquidity_growth_bps <= 10_000, MiniPumpError::InvalidBasisPoints);
+
+        self.global_state.liquidity_growth_bps = liquidity_growth_bps;
+
+        Ok(())
+    }
+
+    /// Sets the minimum seconds that must pass between two owner config changes
+    ///
+    /// Bounds how fast a compromised owner key can retune the protocol. The change itself
+    /// is subject to the current cooldown. Zero disables it, and it can be at most
+    /// `MAX_CONFIG_CHANGE_COOLDOWN`, so a typo cannot lock the owner out of the config.
+    pub fn set_config_change_cooldown(&mut self, config_change_cooldown: i64) -> Result<()> {
+        self.only_owner()?;
+
+        require!(
+            (0..=MAX_CONFIG_CHANGE_COOLDOWN).contains(&config_change_cooldown),
+            MiniPumpError::InvalidCooldown
+        );
+        self.global_state.config_change_cooldown = config_change_cooldown;
+
+        Ok(())
+    }
+
+    /// Records the key governance intends to hand the program upgrade authority to
+    ///
+    /// The upgrade authority itself is a loader setting this program cannot change; the target
+    /// only publishes the intended handoff so tooling can coordinate it. Emits an
+    /// `UpgradeAuthorityTargetSet` event.
+    pub fn set_upgrade_authority_target(&mut self, upgrade_authority_target: Pubkey) -> Result<()> {
+        self.only_owner()?;
+
+        let old_target = self.global_state.upgrade_authority_target;
+        self.global_state.upgrade_authority_target = upgrade_authority_target;
+
+        emit!(UpgradeAuthorityTargetSet {
+            old_target,
+            new_target: upgrade_authority_target,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Sets the lamports a buyer must hold beyond the purchase amount to buy
+    ///
+    /// A crude but cheap deterrent against freshly funded sybil wallets. Zero disables it.
+    pub fn set_min_buyer_lamports(&mut self, min_buyer_lamports: u64) -> Result<()> {
+        self.only_owner()?;
+
+        self.global_state.min_buyer_lamports = min_buyer_lamports;
+
+        Ok(())
+    }
+
+    /// Selects the full `TradeEvent` or the compact `TradeEventMinimal` for every trade
+    ///
+    /// The compact event saves log space on busy curves at the cost of the post-trade state.
+    pub fn set_event_verbosity(&mut self, event_verbosity: u8) -> Result<()> {
+        self.only_owner()?;
+
+        require!(event_verbosity <= EVENT_VERBOSITY_MINIMAL, MiniPumpError::InvalidEventVerbosity);
+
+        self.global_state.event_verbosity = event_verbosity;
+
+        Ok(())
+    }
+
+    /// Pauses or resumes new launches without affecting trading on existing curves
+    pub fn set_launches_paused(&mut self, launches_paused: bool) -> Result<()> {
+        self.only_owner()?;
+
+        self.global_state.launches_paused = launches_paused;
+
+        emit!(PauseToggled {
+            scope: PauseScope::Launches,
+            paused: launches_paused,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Sets how the supply of new curves splits between the curve sale and migration
+    ///
+    /// Both amounts are in base units at `TOKEN_DECIMALS`. Each new curve mints
+    /// `total_supply`, sells `tokens_to_sell` and keeps the difference for the DEX pool.
+    /// The supply must fit `max_total_supply_per_launch` and the sale must fit the default
+    /// virtual token liquidity, so every later launch can mint and sell it. Existing curves
+    /// keep the split they launched with.
+    pub fn set_launch_supply_split(&mut self, total_supply: u64, tokens_to_sell: u64) -> Result<()> {
+        self.only_owner()?;
+
+        self.check_launch_supply(total_supply, tokens_to_sell, self.global_state.virtual_token_liquidity)?;
+
+        self.global_state.total_tokens_to_mint = total_supply;
+        self.global_state.tokens_to_sell = tokens_to_sell;
+
+        Ok(())
+    }
+
+    /// Checks a launch supply split and the supply ceiling launches enforce
+    fn check_launch_supply(&self, total_supply: u64, tokens_to_sell: u64, virtual_token_liquidity: u64) -> Result<()> {
+        GlobalState::validate_supply_split(total_supply, tokens_to_sell, virtual_token_liquidity)?;
+
+        let max_total_supply = self.global_state.max_total_supply_per_launch;
+        require!(
+            max_total_supply == 0 || total_supply <= raw_token_amount(max_total_supply, TOKEN_DECIMALS)?,
+            MiniPumpError::SupplyTooLarge
+        );
+
+        Ok(())
+    }
+
+    /// Sets how many curves may be trading at once
+    ///
+    /// Zero disables the limit. Curves already trading beyond a lowered cap are unaffected,
+    /// but no new curve launches until enough of them graduate.
+    pub fn set_max_active_curves(&mut self, max_active_curves: u64) -> Result<()> {
+        self.only_owner()?;
+
+        self.global_state.max_active_curves = max_active_curves;
+
+        Ok(())
+    }
+
+    /// Sets the trade fee in basis points of each trade's SOL leg
+    ///
+    /// The fee is sent to the fee vault and never enters the curve's reserves. Curves read
+    /// it at trade time, so the change applies to existing curves at once. It is capped as
+    /// in `init_protocol`, and zero disables it.
+    pub fn set_fee(&mut self, fee_basis_points: u16) -> Result<()> {
+        self.only_owner()?;
+
+        GlobalState::validate_fee_basis_points(fee_basis_points)?;
+
+        self.global_state.fee_basis_points = fee_basis_points;
+
+        Ok(())
+    }
+
+    /// Chooses whether buys pay the trade fee in tokens instead of SOL
+    ///
+    /// In token mode a buy's whole SOL leg enters the reserve and the curve keeps
+    /// `fee_basis_points` of the tokens it prices, so the buyer receives fewer tokens than
+    /// a SOL-fee buy of the same size. The kept tokens stay unsold, so the token reserve
+    /// only falls by what the buyer receives: the buy lifts the price a little more than
+    /// the same buy paying in SOL, and k grows with every buy, raising the curve's floor
+    /// like the liquidity growth share. Nothing reaches the fee vault on buys, and sells
+    /// still pay the fee in SOL.
+    pub fn set_fee_in_token(&mut self, fee_in_token: bool) -> Result<()> {
+        self.only_owner()?;
+
+        self.global_state.fee_in_token = fee_in_token;
+
+        Ok(())
+    }
+
+    /// Sets the wallets `collect_fees` splits the trade fees between
+    ///
+    /// Takes up to `MAX_FEE_RECIPIENTS` distinct wallets whose shares sum to 10_000 basis
+    /// points. An empty list clears the split, so the owner collects the fees alone.
+    pub fn set_fee_recipients(&mut self, fee_recipients: Vec<FeeRecipient>) -> Result<()> {
+        self.only_owner()?;
+
+        require!(fee_recipients.len() <= MAX_FEE_RECIPIENTS as usize, MiniPumpError::InvalidFeeSplit);
+        let mut total_share_bps = 0u32;
+        for (index, fee_recipient) in fee_recipients.iter().enumerate() {
+            require!(fee_recipient.share_bps > 0 && fee_recipient.recipient != Pubkey::default(), MiniPumpError::InvalidFeeSplit);
+            require!(
+                fee_recipients[..index].iter().all(|other| other.recipient != fee_recipient.recipient),
+                MiniPumpError::InvalidFeeSplit
+            );
+            total_share_bps += fee_recipient.share_bps as u32;
+        }
+        require!(fee_recipients.is_empty() || total_share_bps == 10_000, MiniPumpError::InvalidFeeSplit);
+
+        let mut slots = [FeeRecipient::default(); MAX_FEE_RECIPIENTS as usize];
+        slots[..fee_recipients.len()].copy_from_slice(&fee_recipients);
+        self.global_state.fee_recipients = slots;
+
+        Ok(())
+    }
+
+    /// Pauses or resumes buys and sells on every curve
+    ///
+    /// Launches and withdrawals stay open so the owner can still migrate curves while paused.
+    /// Pausing is exempt from the config change cooldown so trading can be halted at once.
+    pub fn set_paused(&mut self, is_paused: bool) -> Result<()> {
+        self.require_owner()?;
+
+        self.global_state.is_paused = is_paused;
+
+        emit!(PauseToggled {
+            scope: PauseScope::Trading,
+            paused: is_paused,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Proposes a new key as protocol owner
+    ///
+    /// Ownership only moves once the proposed key signs `accept_ownership`, so a mistyped
+    /// key can never take over the protocol. Proposing again replaces the pending key.
+    pub fn transfer_ownership(&mut self, new_owner: Pubkey) -> Result<()> {
+        self.only_owner()?;
+
+        require!(new_owner != Pubkey::default(), MiniPumpError::InvalidNewOwner);
+        self.global_state.pending_owner = new_owner;
+
+        Ok(())
+    }
+
+    /// Verifies the signer is the protocol owner and enforces the config change cooldown
+    ///
+    /// Records the change time, so every setter that goes through here is rate limited.
+    fn only_owner(&mut self) -> Result<()> {
+        self.require_owner()?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let cooldown = self.global_state.config_change_cooldown;
+        if cooldown > 0 {
+            let next_change = self.global_state.last_config_change.saturating_add(cooldown);
+            require!(now >= next_change, MiniPumpError::ConfigChangeTooSoon);
+        }
+        self.global_state.last_config_change = now;
+
+        Ok(())
+    }
+
+    /// Verifies the signer is the protocol owner recorded in the global state
+    fn require_owner(&self) -> Result<()> {
+        require!(self.owner.key() == self.global_state.owner, MiniPumpError::NotOwner);
+
+        Ok(())
+    }
+}
+
+/// # Accept Ownership Instruction
+///
+/// Completes a transfer started by `transfer_ownership`, signed by the proposed owner.
+#[derive(Accounts)]
+pub struct AcceptOwnership<'info> {
+    /// The key proposed by the current owner
+    pub new_owner: Signer<'info>,
+
+    /// The global state account recording the owner
+    #[account(
+        mut,
+        seeds = ["global_state".as_bytes()],
+        bump = global_state.bump,
+    )]
+    pub global_state: Account<'info, GlobalState>,
+}
+
+impl<'info> AcceptOwnership<'info> {
+    /// Makes the pending owner the protocol owner
+    ///
+    /// Emits an `OwnershipTransferred` event so monitoring tools can alert on
+    /// admin key changes.
+    pub fn accept_ownership(&mut self) -> Result<()> {
+        let new_owner = self.new_owner.key();
+        require!(new_owner == self.global_state.pending_owner, MiniPumpError::NotPendingOwner);
+
+        let old_owner = self.global_state.owner;
+        self.global_state.owner = new_owner;
+        self.global_state.pending_owner = Pubkey::default();
+
+        emit!(OwnershipTransferred {
+            old_owner,
+            new_owner,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+/// What a pause switch halts
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum PauseScope {
+    /// Buys and sells on every curve, set by `set_paused`
+    Trading,
+    /// New launches, set by `set_launches_paused`
+    Launches,
+}
+
+/// Event emitted when the owner pauses or resumes trading or launches
+#[event]
+pub struct PauseToggled {
+    /// Which switch changed
+    pub scope: PauseScope,
+    /// Whether that scope is paused after the change
+    pub paused: bool,
+    /// Unix timestamp of the change
+    pub timestamp: i64,
+}
+
+/// Event emitted when protocol ownership changes hands
+#[event]
+pub struct OwnershipTransferred {
+    /// The owner before the transfer
+    pub old_owner: Pubkey,
+    /// The owner after the transfer
+    pub new_owner: Pubkey,
+    /// Unix timestamp of the transfer
+    pub timestamp: i64,
+}
+
+/// Event emitted when the default virtual reserves for new curves change
+#[event]
+pub struct CurveDefaultsUpdated {
+    /// Virtual SOL liquidity before the update
+    pub old_vsol: u64,
+    /// Virtual token liquidity before the update
+    pub old_vtoken: u64,
+    /// Virtual SOL liquidity after the update
+    pub new_vsol: u64,
+    /// Virtual token liquidity after the update
+    pub new_vtoken: u64,
+    /// Unix timestamp of the update
+    pub timestamp: i64,
+}
+
+/// Event emitted when the recorded upgrade authority target changes
+#[event]
+pub struct UpgradeAuthorityTargetSet {
+    /// The target before the update, the default key if none was set
+    pub old_target: Pubkey,
+    /// The target after the update
+    pub new_target: Pubkey,
+    /// Unix timestamp of the update
+    pub timestamp: i64,
+}
@@ -5,13 +5,15 @@ use anchor_spl::{
 };
 use anchor_lang::system_program::{transfer, Transfer};
 
+use crate::errors::MiniPumpError;
+use crate::instructions::deactivate_curve::end_trading;
 use crate::state::global_state::GlobalState;
 use crate::state::bonding_curve::BondingCurve;
 
 /// # Withdraw Funds Instruction
 ///
 /// This instruction enables the protocol owner to withdraw all SOL and remaining tokens 
-/// (up to 200 million) from a deactivated bonding curve. The primary purpose is to migrate 
+/// (the curve's migration reserve plus anything unsold) from a deactivated bonding curve. The primary purpose is to migrate 
 /// these assets to a decentralized exchange like Raydium to establish a liquidity pool.
 ///
 /// ## Purpose and Lifecycle
@@ -34,6 +36,7 @@ pub struct WithdrawFunds<'info> {
 
     /// The global state account containing protocol ownership information
     #[account(
+        mut,
         seeds = ["global_state".as_bytes()],
         bump = global_state.bump,
     )]
@@ -42,6 +45,7 @@ pub struct WithdrawFunds<'info> {
     /// The SOL escrow account that holds all SOL collected during bonding curve operations
     /// All SOL will be withdrawn to create the SOL side of the DEX liquidity pool
     #[account(
+        mut,
         seeds = ["bonding_curve_sol_escrow".as_bytes(), bonding_curve.key().as_ref()],
         bump,
     )]
@@ -50,16 +54,18 @@ pub struct WithdrawFunds<'info> {
     /// The bonding curve account that must be inactive before migration
     /// Contains state information about the token's bonding curve
     #[account(
+        mut,
         seeds = ["bonding_curve".as_bytes(), token_mint.key().as_ref()],
         bump = bonding_curve.bump,
     )]
     pub bonding_curve: Account<'info, BondingCurve>,
 
-    /// The token account owned by the bonding curve
-    /// Contains the remaining tokens (up to 200 million) that will form the token side of the DEX liquidity pool
+    /// The bonding curve's associated token account, the same one `launch_coin` mints into
+    /// Contains the remaining tokens (the migration reserve plus anything unsold) that will form the token side of the DEX liquidity pool
     #[account(
-        seeds = ["bonding_curve_token_account".as_bytes(), bonding_curve.key().as_ref()],
-        bump,
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = bonding_curve,
     )]
     pub bonding_curve_token_account: InterfaceAccount<'info, TokenAccount>,
 
@@ -71,6 +77,14 @@ pub struct WithdrawFunds<'info> {
     )]
     pub payer_token_account: InterfaceAccount<'info, TokenAccount>,
 
+    /// The protocol treasury funding the graduation bonus
+    #[account(
+        mut,
+        seeds = ["treasury".as_bytes()],
+        bump,
+    )]
+    pub treasury: SystemAccount<'info>,
+
     /// The mint of the token that will be paired with SOL in the DEX liquidity pool
     pub token_mint: InterfaceAccount<'info, Mint>,
 
@@ -85,66 +99,116 @@ pub struct WithdrawFunds<'info> {
 }
 
 impl<'info> WithdrawFunds<'info> {
+    /// Deactivates a curve that has sold its full allocation and withdraws its assets in one call
+    ///
+    /// Lets operators migrating many tokens graduate and withdraw each curve with a single
+    /// transaction. Fails if the curve has not reached the sell limit.
+    pub fn graduate_and_withdraw(&mut self, bumps: WithdrawFundsBumps) -> Result<()> {
+        require!(self.bonding_curve.tokens_sold >= self.bonding_curve.tokens_to_sell, MiniPumpError::SellLimitNotReached);
+
+        if self.bonding_curve.is_active {
+            end_trading(&mut self.global_state, &mut self.bonding_curve)?;
+        }
+
+        self.withdraw_funds(bumps)
+    }
+
     /// Withdraws all SOL and remaining tokens from the bonding curve for DEX liquidity migration
     ///
     /// This function performs the complete asset withdrawal needed before creating a DEX liquidity pool:
     /// 1. Transfers all accumulated SOL from the bonding curve escrow to the owner
-    /// 2. Transfers all remaining tokens (up to 200 million) from the bonding curve to the owner
+    /// 2. Pays the configured graduation bonus from the treasury to the owner
+    /// 3. Transfers the migration reserve and any unsold tokens from the bonding curve to the owner
     ///
     /// After this function executes successfully, the owner should:
     /// - Create a liquidity pool on Raydium or another Solana DEX
     /// - Deposit the withdrawn SOL and tokens into the pool
     /// - Enable market-based trading for the token
-    pub fn withdraw_funds(&mut self) -> Result<()> {
+    pub fn withdraw_funds(&mut self, bumps: WithdrawFundsBumps) -> Result<()> {
         // Verify the caller is the protocol owner with migration authority
         require!(self.payer.key() == self.global_state.owner, MiniPumpError::NotOwner);
-        
-        // Ensure there is SOL available to withdraw for the DEX liquidity pool
-        require!(self.sol_escrow.lamports() > 0, MiniPumpError::InsufficientSolBalance);
-        
-        // Confirm the bonding curve is deactivated before migration
-        // This prevents premature liquidity withdrawal that could harm traders
-        require!(!self.bonding_curve.is_active, MiniPumpError::BondingCurveActive);
+
+        check_withdraw_preconditions(&self.global_state, &self.bonding_curve, &self.sol_escrow, &self.treasury)?;
+        let graduation_bonus_sol = self.global_state.graduation_bonus_sol;
 
         // Step 1: Transfer all SOL from the escrow to the owner for DEX liquidity
-        let cpi_ctx = CpiContext::new(self.system_program.to_account_info(), Transfer {
+        // The escrow PDA signs for its own lamports
+        let bonding_curve_key = self.bonding_curve.key();
+        let escrow_seeds = &[
+            "bonding_curve_sol_escrow".as_bytes(),
+            bonding_curve_key.as_ref(),
+            &[bumps.sol_escrow],
+        ];
+        let escrow_signer = &[&escrow_seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(self.system_program.to_account_info(), Transfer {
             from: self.sol_escrow.to_account_info(),
             to: self.payer.to_account_info(),
-        });
+        }, escrow_signer);
 
         transfer(cpi_ctx, self.sol_escrow.lamports())?;
 
-        // Step 2: Transfer all remaining tokens to the owner for DEX liquidity
-        // These tokens (up to 200 million) will form the token side of the DEX pool
-        let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), TransferChecked {
+        // Step 2: Top up the migration proceeds with the graduation bonus from the treasury
+        if graduation_bonus_sol > 0 {
+            let treasury_seeds = &["treasury".as_bytes(), &[bumps.treasury]];
+            let treasury_signer = &[&treasury_seeds[..]];
+
+            let cpi_ctx = CpiContext::new_with_signer(self.system_program.to_account_info(), Transfer {
+                from: self.treasury.to_account_info(),
+                to: self.payer.to_account_info(),
+            }, treasury_signer);
+
+            transfer(cpi_ctx, graduation_bonus_sol)?;
+        }
+
+        // Step 3: Transfer all remaining tokens to the owner for DEX liquidity
+        // These tokens will form the token side of the DEX pool
+        let token_mint_key = self.token_mint.key();
+        let curve_seeds = &[
+            "bonding_curve".as_bytes(),
+            token_mint_key.as_ref(),
+            &[self.bonding_curve.bump],
+        ];
+        let curve_signer = &[&curve_seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), TransferChecked {
             from: self.bonding_curve_token_account.to_account_info(),
             to: self.payer_token_account.to_account_info(),
             mint: self.token_mint.to_account_info(),
             authority: self.bonding_curve.to_account_info(),
-        });
+        }, curve_signer);
+
+        // Everything minted at launch that buyers did not take: the migration reserve plus any unsold tokens
+        let tokens_remaining = self.bonding_curve.total_supply.checked_sub(self.bonding_curve.tokens_sold).ok_or(MiniPumpError::ArithmeticOverflow)?;
+        transfer_checked(cpi_ctx, tokens_remaining, self.token_mint.decimals)?;
+
+        // Block every later operation on the emptied curve
+        self.bonding_curve.migrated = true;
 
-        // Calculate the remaining tokens to transfer
-        // - virtual_token_liquidity is 1 billion (total supply)
-        // - tokens_sold is 800 million (already sold through bonding curve)
-        // - The remaining 200 million tokens are transferred to the owner for DEX liquidity
-        transfer_checked(cpi_ctx, self.bonding_curve.virtual_token_liquidity - self.bonding_curve.tokens_sold, self.token_mint.decimals)?;
         Ok(())
     }
 }
 
-/// Error codes specific to the DEX migration withdrawal process
-#[error_code]
-pub enum MiniPumpError {
-    /// Returned when someone other than the protocol owner attempts migration
-    #[msg("Not owner")]
-    NotOwner,
-    
-    /// Returned when there is insufficient SOL for meaningful DEX liquidity
-    #[msg("Insufficient SOL balance")]
-    InsufficientSolBalance,
-    
-    /// Returned when attempting to migrate from an active bonding curve
-    /// Migration must only occur after the bonding curve phase is complete
-    #[msg("Bonding curve is active")]
-    BondingCurveActive,
+/// Checks the curve, escrow and treasury preconditions of a withdrawal
+///
+/// Shared with the `is_withdrawable` view so the two can never disagree.
+pub fn check_withdraw_preconditions(global_state: &GlobalState, bonding_curve: &BondingCurve, sol_escrow: &AccountInfo, treasury: &AccountInfo) -> Result<()> {
+    // A curve's assets can only be migrated once
+    require!(!bonding_curve.migrated, MiniPumpError::CurveMigrated);
+
+    // Ensure there is SOL available to withdraw for the DEX liquidity pool
+    require!(sol_escrow.lamports() > 0, MiniPumpError::InsufficientSolBalance);
+
+    // Confirm the bonding curve is deactivated before migration
+    // This prevents premature liquidity withdrawal that could harm traders
+    require!(!bonding_curve.is_active, MiniPumpError::BondingCurveActive);
+
+    // A curve deactivated before it sold anything would migrate a pool with no SOL behind it
+    require!(bonding_curve.tokens_sold > 0, MiniPumpError::NothingToMigrate);
+
+    // Make sure the treasury can fund the bonus without dropping below rent exemption
+    let treasury_available = treasury.lamports().saturating_sub(Rent::get()?.minimum_balance(0));
+    require!(treasury_available >= global_state.graduation_bonus_sol, MiniPumpError::InsufficientSolBalance);
+
+    Ok(())
 }
@@ -0,0 +1,57 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+
+use crate::errors::MiniPumpError;
+use crate::state::global_state::GlobalState;
+
+/// # Withdraw Treasury Instruction
+///
+/// Owner-only instruction collecting the launch fees accumulated in the treasury PDA.
+/// The treasury keeps its rent-exempt minimum so it stays open for later launch fees
+/// and graduation bonuses.
+#[derive(Accounts)]
+pub struct WithdrawTreasury<'info> {
+    /// The protocol owner receiving the withdrawn SOL
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// The global state account recording the protocol owner
+    #[account(
+        seeds = ["global_state".as_bytes()],
+        bump = global_state.bump,
+    )]
+    pub global_state: Account<'info, GlobalState>,
+
+    /// The protocol treasury holding the launch fees
+    #[account(
+        mut,
+        seeds = ["treasury".as_bytes()],
+        bump,
+    )]
+    pub treasury: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> WithdrawTreasury<'info> {
+    /// Moves `amount` lamports from the treasury to the owner
+    ///
+    /// Fails if the withdrawal would take the treasury below its rent-exempt minimum.
+    pub fn withdraw_treasury(&mut self, amount: u64, bumps: WithdrawTreasuryBumps) -> Result<()> {
+        require!(self.owner.key() == self.global_state.owner, MiniPumpError::NotOwner);
+        require!(amount > 0, MiniPumpError::InvalidSolAmount);
+
+        let available = self.treasury.lamports().saturating_sub(Rent::get()?.minimum_balance(0));
+        require!(amount <= available, MiniPumpError::InsufficientSolBalance);
+
+        let treasury_seeds = &["treasury".as_bytes(), &[bumps.treasury]];
+        let treasury_signer = &[&treasury_seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(self.system_program.to_account_info(), Transfer {
+            from: self.treasury.to_account_info(),
+            to: self.owner.to_account_info(),
+        }, treasury_signer);
+
+        transfer(cpi_ctx, amount)
+    }
+}
@@ -35,10 +35,14 @@
 
 use anchor_lang::prelude::*;
 
+pub mod constants;
+pub mod errors;
 pub mod instructions;
+pub mod math;
 pub mod state;
 
 use instructions::*;
+use state::FeeRecipient;
 
 declare_id!("GgumMKBeidaDAeMFHxP4ejUsoHBkMYnihxLCzVzpNJzv");
 
@@ -46,23 +50,283 @@ declare_id!("GgumMKBeidaDAeMFHxP4ejUsoHBkMYnihxLCzVzpNJzv");
 pub mod mini_pump {
     use super::*;
 
-    pub fn init_protocol(ctx: Context<InitProtocol>, total_tokens_to_mint: u64, virtual_sol_liquidity: u64, virtual_token_liquidity: u64, tokens_to_sell: Pubkey) -> Result<()> {
-        ctx.accounts.init_protocol(total_tokens_to_mint, virtual_sol_liquidity, virtual_token_liquidity, tokens_to_sell, ctx.bumps)
+    pub fn init_protocol(ctx: Context<InitProtocol>, total_tokens_to_mint: u64, virtual_sol_liquidity: u64, virtual_token_liquidity: u64, tokens_to_sell: u64, fee_basis_points: u16) -> Result<()> {
+        ctx.accounts.init_protocol(total_tokens_to_mint, virtual_sol_liquidity, virtual_token_liquidity, tokens_to_sell, fee_basis_points, ctx.bumps)
     }
 
     pub fn launch_coin(ctx: Context<LaunchCoin>, name: String, symbol: String, uri: String) -> Result<()> {
         ctx.accounts.launch_coin( name, symbol, uri, ctx.bumps)
     }
 
-    pub fn buy_token(ctx: Context<TradeCoin>, sol_amount: u64) -> Result<()> {
-        ctx.accounts.buy_token(sol_amount)
+    pub fn launch_and_buy(ctx: Context<LaunchAndBuy>, name: String, symbol: String, uri: String, sol_amount: u64, min_tokens_out: u64) -> Result<()> {
+        ctx.accounts.launch_and_buy(name, symbol, uri, sol_amount, min_tokens_out, ctx.bumps)
     }
 
-    pub fn sell_token(ctx: Context<TradeCoin>, token_amount: u64) -> Result<()> {
-        ctx.accounts.sell_token(token_amount)
+    pub fn create_buyer_ata(_ctx: Context<CreateBuyerAta>) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn buy_token(ctx: Context<TradeCoin>, sol_amount: u64, max_price: u64, expected_epoch: Option<u64>, slippage: Option<Slippage>) -> Result<()> {
+        ctx.accounts.buy_token(sol_amount, max_price, expected_epoch, slippage, ctx.bumps)
+    }
+
+    pub fn sell_token(ctx: Context<TradeCoin>, token_amount: u64, min_sol_out: u64, slippage: Option<Slippage>) -> Result<()> {
+        ctx.accounts.sell_token(token_amount, min_sol_out, slippage, ctx.bumps)
+    }
+
+    pub fn sell_basket<'info>(ctx: Context<'_, '_, 'info, 'info, SellBasket<'info>>, token_amounts: Vec<u64>, min_sol_outs: Vec<u64>) -> Result<()> {
+        ctx.accounts.sell_basket(ctx.remaining_accounts, token_amounts, min_sol_outs)
     }
 
     pub fn withdraw_funds(ctx: Context<WithdrawFunds>) -> Result<()> {
-        ctx.accounts.withdraw_funds()
+        ctx.accounts.withdraw_funds(ctx.bumps)
+    }
+
+    pub fn graduate_and_withdraw(ctx: Context<WithdrawFunds>) -> Result<()> {
+        ctx.accounts.graduate_and_withdraw(ctx.bumps)
+    }
+
+    pub fn deactivate_curve(ctx: Context<DeactivateCurve>) -> Result<()> {
+        ctx.accounts.deactivate_curve()
+    }
+
+    pub fn compute_reserves_for_market_cap(ctx: Context<ComputeReserves>, target_mcap: u64, supply: u64) -> Result<SuggestedReserves> {
+        ctx.accounts.compute_reserves_for_market_cap(target_mcap, supply)
+    }
+
+    pub fn set_min_tokens_per_buy(ctx: Context<UpdateGlobalParams>, min_tokens_per_buy: u64) -> Result<()> {
+        ctx.accounts.set_min_tokens_per_buy(min_tokens_per_buy)
+    }
+
+    pub fn set_enforce_unique_symbols(ctx: Context<UpdateGlobalParams>, enforce_unique_symbols: bool) -> Result<()> {
+        ctx.accounts.set_enforce_unique_symbols(enforce_unique_symbols)
+    }
+
+    pub fn set_launch_fee(ctx: Context<UpdateGlobalParams>, launch_fee: u64) -> Result<()> {
+        ctx.accounts.set_launch_fee(launch_fee)
+    }
+
+    pub fn set_max_uri_len(ctx: Context<UpdateGlobalParams>, max_uri_len: u16) -> Result<()> {
+        ctx.accounts.set_max_uri_len(max_uri_len)
+    }
+
+    pub fn set_graduation_bonus_sol(ctx: Context<UpdateGlobalParams>, graduation_bonus_sol: u64) -> Result<()> {
+        ctx.accounts.set_graduation_bonus_sol(graduation_bonus_sol)
+    }
+
+    pub fn set_whale_threshold_sol(ctx: Context<UpdateGlobalParams>, whale_threshold_sol: u64) -> Result<()> {
+        ctx.accounts.set_whale_threshold_sol(whale_threshold_sol)
+    }
+
+    pub fn set_max_total_supply_per_launch(ctx: Context<UpdateGlobalParams>, max_total_supply_per_launch: u64) -> Result<()> {
+        ctx.accounts.set_max_total_supply_per_launch(max_total_supply_per_launch)
+    }
+
+    pub fn set_min_virtual_token_liquidity(ctx: Context<UpdateGlobalParams>, min_virtual_token_liquidity: u64) -> Result<()> {
+        ctx.accounts.set_min_virtual_token_liquidity(min_virtual_token_liquidity)
+    }
+
+    pub fn set_sell_penalty(ctx: Context<UpdateGlobalParams>, sell_penalty_bps: u16, sell_penalty_window: i64) -> Result<()> {
+        ctx.accounts.set_sell_penalty(sell_penalty_bps, sell_penalty_window)
+    }
+
+    pub fn set_curve_defaults(ctx: Context<UpdateGlobalParams>, virtual_sol_liquidity: u64, virtual_token_liquidity: u64) -> Result<()> {
+        ctx.accounts.set_curve_defaults(virtual_sol_liquidity, virtual_token_liquidity)
+    }
+
+    pub fn update_global_params(ctx: Context<UpdateGlobalParams>, virtual_sol_liquidity: u64, virtual_token_liquidity: u64, total_tokens_to_mint: u64) -> Result<()> {
+        ctx.accounts.update_global_params(virtual_sol_liquidity, virtual_token_liquidity, total_tokens_to_mint)
+    }
+
+    pub fn set_max_buy_fraction_bps(ctx: Context<UpdateGlobalParams>, max_buy_fraction_bps: u16) -> Result<()> {
+        ctx.accounts.set_max_buy_fraction_bps(max_buy_fraction_bps)
+    }
+
+    pub fn set_k_tolerance_bps(ctx: Context<UpdateGlobalParams>, k_tolerance_bps: u16) -> Result<()> {
+        ctx.accounts.set_k_tolerance_bps(k_tolerance_bps)
+    }
+
+    pub fn set_min_graduation_sol(ctx: Context<UpdateGlobalParams>, min_graduation_sol: u64) -> Result<()> {
+        ctx.accounts.set_min_graduation_sol(min_graduation_sol)
+    }
+
+    pub fn set_track_entry_prices(ctx: Context<UpdateGlobalParams>, track_entry_prices: bool) -> Result<()> {
+        ctx.accounts.set_track_entry_prices(track_entry_prices)
+    }
+
+    pub fn set_liquidity_growth_bps(ctx: Context<UpdateGlobalParams>, liquidity_growth_bps: u16) -> Result<()> {
+        ctx.accounts.set_liquidity_growth_bps(liquidity_growth_bps)
+    }
+
+    pub fn set_config_change_cooldown(ctx: Context<UpdateGlobalParams>, config_change_cooldown: i64) -> Result<()> {
+        ctx.accounts.set_config_change_cooldown(config_change_cooldown)
+    }
+
+    pub fn set_upgrade_authority_target(ctx: Context<UpdateGlobalParams>, upgrade_authority_target: Pubkey) -> Result<()> {
+        ctx.accounts.set_upgrade_authority_target(upgrade_authority_target)
+    }
+
+    pub fn set_min_buyer_lamports(ctx: Context<UpdateGlobalParams>, min_buyer_lamports: u64) -> Result<()> {
+        ctx.accounts.set_min_buyer_lamports(min_buyer_lamports)
+    }
+
+    pub fn set_event_verbosity(ctx: Context<UpdateGlobalParams>, event_verbosity: u8) -> Result<()> {
+        ctx.accounts.set_event_verbosity(event_verbosity)
+    }
+
+    pub fn set_launches_paused(ctx: Context<UpdateGlobalParams>, launches_paused: bool) -> Result<()> {
+        ctx.accounts.set_launches_paused(launches_paused)
+    }
+
+    pub fn set_launch_supply_split(ctx: Context<UpdateGlobalParams>, total_supply: u64, tokens_to_sell: u64) -> Result<()> {
+        ctx.accounts.set_launch_supply_split(total_supply, tokens_to_sell)
+    }
+
+    pub fn set_max_active_curves(ctx: Context<UpdateGlobalParams>, max_active_curves: u64) -> Result<()> {
+        ctx.accounts.set_max_active_curves(max_active_curves)
+    }
+
+    pub fn set_fee(ctx: Context<UpdateGlobalParams>, fee_basis_points: u16) -> Result<()> {
+        ctx.accounts.set_fee(fee_basis_points)
+    }
+
+    pub fn set_fee_in_token(ctx: Context<UpdateGlobalParams>, fee_in_token: bool) -> Result<()> {
+        ctx.accounts.set_fee_in_token(fee_in_token)
+    }
+
+    pub fn set_fee_recipients(ctx: Context<UpdateGlobalParams>, fee_recipients: Vec<FeeRecipient>) -> Result<()> {
+        ctx.accounts.set_fee_recipients(fee_recipients)
+    }
+
+    pub fn set_paused(ctx: Context<UpdateGlobalParams>, is_paused: bool) -> Result<()> {
+        ctx.accounts.set_paused(is_paused)
+    }
+
+    pub fn transfer_ownership(ctx: Context<UpdateGlobalParams>, new_owner: Pubkey) -> Result<()> {
+        ctx.accounts.transfer_ownership(new_owner)
+    }
+
+    pub fn accept_ownership(ctx: Context<AcceptOwnership>) -> Result<()> {
+        ctx.accounts.accept_ownership()
+    }
+
+    pub fn withdraw_treasury(ctx: Context<WithdrawTreasury>, amount: u64) -> Result<()> {
+        ctx.accounts.withdraw_treasury(amount, ctx.bumps)
+    }
+
+    pub fn collect_fees<'info>(ctx: Context<'_, '_, 'info, 'info, CollectFees<'info>>, amount: u64) -> Result<()> {
+        ctx.accounts.collect_fees(ctx.remaining_accounts, amount, ctx.bumps)
+    }
+
+    pub fn recover_stray_sol(ctx: Context<RecoverStraySol>) -> Result<()> {
+        ctx.accounts.recover_stray_sol()
+    }
+
+    pub fn get_launch_info(ctx: Context<QueryCurve>) -> Result<LaunchInfo> {
+        ctx.accounts.get_launch_info()
+    }
+
+    pub fn get_trade_stats(ctx: Context<QueryCurve>) -> Result<TradeStats> {
+        ctx.accounts.get_trade_stats()
+    }
+
+    pub fn get_timing(ctx: Context<QueryCurve>) -> Result<CurveTiming> {
+        ctx.accounts.get_timing()
+    }
+
+    pub fn get_tokens_remaining(ctx: Context<QueryCurve>) -> Result<u64> {
+        ctx.accounts.get_tokens_remaining()
+    }
+
+    pub fn get_peak_supply(ctx: Context<QueryCurve>) -> Result<u64> {
+        ctx.accounts.get_peak_supply()
+    }
+
+    pub fn get_tokens_remaining_whole(ctx: Context<QueryCurve>) -> Result<u64> {
+        ctx.accounts.get_tokens_remaining_whole()
+    }
+
+    pub fn get_k(ctx: Context<QueryCurve>) -> Result<u128> {
+        ctx.accounts.get_k()
+    }
+
+    pub fn get_avg_entry_price(ctx: Context<QueryPosition>) -> Result<u128> {
+        ctx.accounts.get_avg_entry_price()
+    }
+
+    pub fn get_break_even_sell_tokens(ctx: Context<QueryPosition>) -> Result<BreakEven> {
+        ctx.accounts.get_break_even_sell_tokens()
+    }
+
+    pub fn get_effective_fee(ctx: Context<QueryFee>, amount: u64, is_buy: bool) -> Result<EffectiveFee> {
+        ctx.accounts.get_effective_fee(amount, is_buy)
+    }
+
+    pub fn get_spread_bps(ctx: Context<QueryFee>) -> Result<u64> {
+        ctx.accounts.get_spread_bps()
+    }
+
+    pub fn get_migration_assets(ctx: Context<QueryWithdraw>) -> Result<MigrationAssets> {
+        ctx.accounts.get_migration_assets()
+    }
+
+    pub fn is_withdrawable(ctx: Context<QueryWithdraw>) -> Result<bool> {
+        ctx.accounts.is_withdrawable()
+    }
+
+    pub fn get_state_hash(ctx: Context<QueryCurve>) -> Result<[u8; 32]> {
+        ctx.accounts.get_state_hash()
+    }
+
+    pub fn get_price_q64(ctx: Context<QueryCurve>) -> Result<u128> {
+        ctx.accounts.get_price_q64()
+    }
+
+    pub fn get_price_rounded(ctx: Context<QueryCurve>, tick_size: u64) -> Result<u64> {
+        ctx.accounts.get_price_rounded(tick_size)
+    }
+
+    pub fn min_sol_for_one_token(ctx: Context<QueryCurve>) -> Result<u64> {
+        ctx.accounts.min_sol_for_one_token()
+    }
+
+    pub fn sol_to_graduate(ctx: Context<QueryCurve>) -> Result<u64> {
+        ctx.accounts.sol_to_graduate()
+    }
+
+    pub fn tokens_for_ownership_bps(ctx: Context<QueryCurve>, bps: u16) -> Result<OwnershipQuote> {
+        ctx.accounts.tokens_for_ownership_bps(bps)
+    }
+
+    pub fn get_fdv(ctx: Context<QueryCurve>) -> Result<u64> {
+        ctx.accounts.get_fdv()
+    }
+
+    pub fn get_reserve_ratio(ctx: Context<QueryCurve>) -> Result<u64> {
+        ctx.accounts.get_reserve_ratio()
+    }
+
+    pub fn quote_sell_all(ctx: Context<QuerySellAll>) -> Result<u64> {
+        ctx.accounts.quote_sell_all()
+    }
+
+    pub fn would_graduate(ctx: Context<QueryCurve>, sol_amount: u64) -> Result<bool> {
+        ctx.accounts.would_graduate(sol_amount)
+    }
+
+    pub fn get_curves_summary<'info>(ctx: Context<'_, '_, 'info, 'info, QueryCurves>) -> Result<Vec<CurveSummary>> {
+        ctx.accounts.get_curves_summary(ctx.remaining_accounts)
+    }
+
+    pub fn quote_buy_effective(ctx: Context<QueryCurve>, sol_amount: u64) -> Result<BuyEffective> {
+        ctx.accounts.quote_buy_effective(sol_amount)
+    }
+
+    pub fn quote_price_impact(ctx: Context<QueryCurve>, amount: u64, is_buy: bool) -> Result<u64> {
+        ctx.accounts.quote_price_impact(amount, is_buy)
+    }
+
+    pub fn quote_round_trip(ctx: Context<QueryCurve>, sol_amount: u64) -> Result<u64> {
+        ctx.accounts.quote_round_trip(sol_amount)
     }
 }
@@ -0,0 +1,572 @@
+//! Bonding curve math shared by the trading instructions and the read-only views
+
+use anchor_lang::prelude::*;
+
+use crate::errors::MiniPumpError;
+
+/// Calculates the amount of tokens to be received for a given SOL amount
+/// 
+/// This function implements a modified constant product formula for bonding curves:
+/// 
+/// The formula is derived from the constant product AMM formula: x * y = k
+/// where x = virtual_sol_liquidity and y = virtual_token_liquidity
+/// 
+/// For a bonding curve with virtual liquidity, we use:
+/// virtual_sol_liquidity * virtual_token_liquidity = constant (k)
+/// 
+/// When a user buys tokens with SOL:
+/// (virtual_sol_liquidity + sol_amount) * new_token_supply = k
+/// 
+/// Since k = virtual_sol_liquidity * virtual_token_liquidity, we can substitute:
+/// (virtual_sol_liquidity + sol_amount) * new_token_supply = virtual_sol_liquidity * virtual_token_liquidity
+/// 
+/// Solving for new_token_supply:
+/// new_token_supply = (virtual_sol_liquidity * virtual_token_liquidity) / (virtual_sol_liquidity + sol_amount)
+/// 
+/// The tokens sent to the user are:
+/// token_amount = virtual_token_liquidity - new_token_supply
+/// 
+/// This creates a price curve that increases as more tokens are purchased,
+/// since the virtual_token_liquidity decreases with each purchase while
+/// virtual_sol_liquidity increases, making each subsequent token more expensive.
+/// 
+/// Price curve visualization:
+/// 
+///  Price
+///    ^
+///    |                                  /|
+///    |                                /
+///    |                              /
+///    |                            /
+///    |                         /
+///    |                      /
+///    |                   /
+///    |               /
+///    |          _/
+///    |____----
+///    +------------------------------------> Tokens Sold
+///
+/// As more tokens are sold, the price increases exponentially due to the
+/// constant product formula, creating a natural price discovery mechanism.
+pub fn calculate_token_for_sol(virtual_sol_liquidity: u64, virtual_token_liquidity: u64, sol_amount: u64) -> Result<u64> {
+    // Calculate new token supply after adding SOL to the virtual liquidity
+    // Formula: new_token_supply = virtual_sol_liquidity * virtual_token_liquidity / (virtual_sol_liquidity + sol_amount)
+    // Rounded up so the tokens sent out round down and the curve never pays out more than k allows
+    // Computed in u128, where neither the product nor the sum can overflow
+    let denominator = virtual_sol_liquidity as u128 + sol_amount as u128;
+    require!(denominator > 0, MiniPumpError::CalculationError);
+    let new_token_supply = constant_product(virtual_sol_liquidity, virtual_token_liquidity).div_ceil(denominator);
+    
+    // The tokens to send out are the difference between current virtual token liquidity and new token supply
+    let token_amount = (virtual_token_liquidity as u128).checked_sub(new_token_supply).ok_or(MiniPumpError::CalculationError)?;
+    
+    u64::try_from(token_amount).map_err(|_| error!(MiniPumpError::ArithmeticOverflow))
+}
+
+/// Calculates the amount of SOL to be received for a given token amount
+/// 
+/// This function implements the inverse of the modified constant product formula:
+/// 
+/// Starting with the constant product formula: x * y = k
+/// where x = virtual_sol_liquidity and y = virtual_token_liquidity
+/// 
+/// When a user sells tokens:
+/// new_sol_supply * (virtual_token_liquidity + token_amount) = k
+/// 
+/// Since k = virtual_sol_liquidity * virtual_token_liquidity, we can substitute:
+/// new_sol_supply * (virtual_token_liquidity + token_amount) = virtual_sol_liquidity * virtual_token_liquidity
+/// 
+/// Solving for new_sol_supply:
+/// new_sol_supply = (virtual_sol_liquidity * virtual_token_liquidity) / (virtual_token_liquidity + token_amount)
+/// 
+/// The SOL sent to the user is:
+/// sol_amount = virtual_sol_liquidity - new_sol_supply
+/// 
+/// This creates a price curve that decreases as more tokens are sold,
+/// since the virtual_token_liquidity increases with each sale while
+/// virtual_sol_liquidity decreases, making each subsequent token less valuable.
+/// 
+/// Price curve for selling visualization:
+/// 
+///  SOL Received
+///    ^
+///    |\
+///    | \
+///    |  \
+///    |   \
+///    |    \
+///    |     \
+///    |      \
+///    |       \
+///    |        \__
+///    |           ----___________
+///    +------------------------------------> Tokens Sold Back
+///
+/// When selling tokens back to the curve, the amount of SOL received
+/// decreases as more tokens are sold, following the inverse of the
+/// bonding curve formula. This creates a natural disincentive for
+/// large sell-offs and helps stabilize the token price.
+pub fn calculate_sol_for_token(virtual_sol_liquidity: u64, virtual_token_liquidity: u64, token_amount: u64) -> Result<u64> {
+    // Calculate new SOL supply after adding tokens to the virtual liquidity
+    // Formula: new_sol_supply = virtual_sol_liquidity * virtual_token_liquidity / (virtual_token_liquidity + token_amount)
+    // Rounded up so the SOL sent out rounds down and the curve never pays out more than k allows
+    // Computed in u128, where neither the product nor the sum can overflow
+    let denominator = virtual_token_liquidity as u128 + token_amount as u128;
+    require!(denominator > 0, MiniPumpError::CalculationError);
+    let new_sol_supply = constant_product(virtual_sol_liquidity, virtual_token_liquidity).div_ceil(denominator);
+    
+    // The SOL to send out is the difference between current virtual SOL liquidity and new SOL supply
+    let sol_amount = (virtual_sol_liquidity as u128).checked_sub(new_sol_supply).ok_or(MiniPumpError::CalculationError)?;
+    
+    u64::try_from(sol_amount).map_err(|_| error!(MiniPumpError::ArithmeticOverflow))
+}
+
+/// Calculates the share of a trade's SOL kept in the virtual SOL reserve as liquidity growth
+///
+/// growth = sol_amount * growth_bps / 10_000, rounded down
+///
+/// On a buy only `sol_amount - growth` is priced against the curve while the full amount
+/// enters the reserve; on a sell the seller is paid `sol_amount - growth` and only that
+/// leaves the reserve. Either way the growth is real SOL left in the escrow, so the curve
+/// stays solvent. It works like an AMM fee retained in the pool: k grows with every trade,
+/// so a buy followed by selling the same tokens leaves the virtual SOL reserve higher at the
+/// same token reserve. Cumulatively the floor price rises by roughly
+/// `traded_sol * growth_bps / 10_000 / virtual_token_liquidity` across all trades.
+pub fn calculate_liquidity_growth(sol_amount: u64, growth_bps: u16) -> Result<u64> {
+    let growth = sol_amount as u128 * growth_bps as u128 / 10_000;
+
+    u64::try_from(growth).map_err(|_| error!(MiniPumpError::ArithmeticOverflow))
+}
+
+/// Calculates the trade fee taken from a trade's SOL leg
+///
+/// fee = sol_amount * fee_bps / 10_000, rounded down
+pub fn calculate_trade_fee(sol_amount: u64, fee_bps: u16) -> Result<u64> {
+    let fee = sol_amount as u128 * fee_bps as u128 / 10_000;
+
+    u64::try_from(fee).map_err(|_| error!(MiniPumpError::ArithmeticOverflow))
+}
+
+/// Calculates the smallest buy that still has `net_amount` lamports left once the trade fee is taken
+///
+/// gross = ceil(net_amount * 10_000 / (10_000 - fee_bps)), the inverse of `calculate_trade_fee`.
+pub fn gross_up_for_trade_fee(net_amount: u64, fee_bps: u16) -> Result<u64> {
+    require!(fee_bps < 10_000, MiniPumpError::InvalidBasisPoints);
+
+    let gross = (net_amount as u128 * 10_000).div_ceil(10_000 - fee_bps as u128);
+
+    u64::try_from(gross).map_err(|_| error!(MiniPumpError::ArithmeticOverflow))
+}
+
+/// Combines two fees taken one after the other into a single rate, in basis points
+///
+/// The second fee is charged on what the first leaves, so the combined rate is
+/// first + second - first * second / 10000, rounded down.
+pub fn compound_fee_bps(first_bps: u16, second_bps: u16) -> u16 {
+    let (first, second) = (first_bps as u32, second_bps as u32);
+
+    (first + second - first * second / 10_000).min(10_000) as u16
+}
+
+/// Calculates the spread between the effective buy and sell price, in basis points of the buy price
+///
+/// A buy pays `buy_fee_bps` of its SOL, then `buy_growth_bps` of the rest, so it pays
+/// spot / ((1 - fee) * (1 - growth)) per token, while a sell receives spot * (1 - sell_fee).
+/// The spot price cancels, so for a marginal trade:
+/// spread = 1 - (1 - fee) * (1 - growth) * (1 - sell_fee), rounded down.
+pub fn spread_bps(buy_fee_bps: u16, buy_growth_bps: u16, sell_fee_bps: u16) -> u64 {
+    let kept = |bps: u16| 10_000u128.saturating_sub(bps as u128);
+    let round_trip_kept = kept(buy_fee_bps) * kept(buy_growth_bps) * kept(sell_fee_bps);
+
+    ((1_000_000_000_000 - round_trip_kept) / 100_000_000) as u64
+}
+
+/// Calculates the smallest buy, in lamports, that receives at least `token_out` tokens
+///
+/// Inverts `calculate_token_for_sol` including its rounding: the buy receives `token_out`
+/// once `virtual_sol_liquidity + priced_sol >= ceil(k / (virtual_token_liquidity - token_out))`.
+/// The result is then grossed up for the liquidity growth share, which is not priced.
+pub fn calculate_sol_for_token_out(virtual_sol_liquidity: u64, virtual_token_liquidity: u64, token_out: u64, growth_bps: u16) -> Result<u64> {
+    require!(token_out < virtual_token_liquidity, MiniPumpError::InsufficientTokenBalance);
+    require!(growth_bps < 10_000, MiniPumpError::InvalidBasisPoints);
+
+    let token_after = (virtual_token_liquidity - token_out) as u128;
+    let priced_sol = constant_product(virtual_sol_liquidity, virtual_token_liquidity).div_ceil(token_after) - virtual_sol_liquidity as u128;
+    let sol_amount = (priced_sol * 10_000).div_ceil(10_000 - growth_bps as u128);
+
+    u64::try_from(sol_amount).map_err(|_| error!(MiniPumpError::ArithmeticOverflow))
+}
+
+/// Calculates the fewest tokens a sell must offer to be paid at least `sol_out` lamports
+///
+/// Inverts `calculate_sol_for_token` including its rounding: the seller receives `sol_priced`
+/// once `virtual_token_liquidity + token_amount >= ceil(k / (virtual_sol_liquidity - sol_priced))`,
+/// where `sol_priced` is `sol_out` grossed up for the liquidity growth share kept in the reserve.
+pub fn calculate_tokens_for_sol_out(virtual_sol_liquidity: u64, virtual_token_liquidity: u64, sol_out: u64, growth_bps: u16) -> Result<u64> {
+    require!(growth_bps < 10_000, MiniPumpError::InvalidBasisPoints);
+
+    let sol_priced = (sol_out as u128 * 10_000).div_ceil(10_000 - growth_bps as u128);
+    require!(sol_priced < virtual_sol_liquidity as u128, MiniPumpError::InsufficientSolBalance);
+
+    let sol_after = virtual_sol_liquidity as u128 - sol_priced;
+    let token_amount = constant_product(virtual_sol_liquidity, virtual_token_liquidity).div_ceil(sol_after).saturating_sub(virtual_token_liquidity as u128);
+
+    u64::try_from(token_amount).map_err(|_| error!(MiniPumpError::ArithmeticOverflow))
+}
+
+/// Calculates the early-sell penalty withheld from a sell's SOL proceeds
+///
+/// The penalty starts at `penalty_bps` of the proceeds right after the wallet's last buy
+/// and decays linearly to zero once `window` seconds have elapsed:
+/// penalty = sol_amount * penalty_bps * (window - elapsed) / (window * 10_000)
+///
+/// Rounded down so the seller is never charged more than the configured rate.
+pub fn calculate_sell_penalty(sol_amount: u64, penalty_bps: u16, window: i64, elapsed: i64) -> Result<u64> {
+    if penalty_bps == 0 || window <= 0 || elapsed >= window {
+        return Ok(0);
+    }
+
+    let remaining = (window - elapsed.max(0)) as u128;
+    let penalty = sol_amount as u128 * penalty_bps as u128 * remaining / (window as u128 * 10_000);
+
+    u64::try_from(penalty).map_err(|_| error!(MiniPumpError::ArithmeticOverflow))
+}
+
+/// Returns the sell penalty rate in basis points after `elapsed` seconds of decay
+///
+/// penalty_bps * (window - elapsed) / window, rounded down. This is the rate
+/// `calculate_sell_penalty` applies, for display; the SOL amount is computed directly.
+pub fn effective_sell_penalty_bps(penalty_bps: u16, window: i64, elapsed: i64) -> u16 {
+    if penalty_bps == 0 || window <= 0 || elapsed >= window {
+        return 0;
+    }
+
+    let remaining = (window - elapsed.max(0)) as u128;
+    (penalty_bps as u128 * remaining / window as u128) as u16
+}
+
+/// Calculates the virtual token liquidity that starts a curve at `market_cap`
+///
+/// The starting market cap is the spot price times the supply:
+/// market_cap = virtual_sol_liquidity * supply / virtual_token_liquidity
+///
+/// Solving for the token side with the SOL side fixed:
+/// virtual_token_liquidity = virtual_sol_liquidity * supply / market_cap
+///
+/// Rounded down, so the curve never starts below the requested market cap.
+pub fn virtual_token_for_market_cap(virtual_sol_liquidity: u64, supply: u64, market_cap: u64) -> Result<u64> {
+    require!(market_cap > 0, MiniPumpError::InvalidSolAmount);
+
+    let virtual_token_liquidity = constant_product(virtual_sol_liquidity, supply) / market_cap as u128;
+    require!(virtual_token_liquidity > 0, MiniPumpError::CalculationError);
+
+    u64::try_from(virtual_token_liquidity).map_err(|_| error!(MiniPumpError::ArithmeticOverflow))
+}
+
+/// Returns the spot price in lamports per token base unit as a Q64.64 fixed-point number
+///
+/// price = virtual_sol_liquidity / virtual_token_liquidity, scaled by 2^64
+///
+/// Early in the curve the price is far below one lamport per base unit, so plain integer
+/// division rounds it to zero. The upper 64 bits hold the integer part and the lower
+/// 64 bits the fraction. Only views use this; transfers stay in integer lamports.
+pub fn price_q64(virtual_sol_liquidity: u64, virtual_token_liquidity: u64) -> Result<u128> {
+    ((virtual_sol_liquidity as u128) << 64)
+        .checked_div(virtual_token_liquidity as u128)
+        .ok_or(error!(MiniPumpError::CalculationError))
+}
+
+/// Returns the spot price in lamports per whole token, rounded to the nearest `tick_size`
+///
+/// price = virtual_sol_liquidity * 10^decimals / virtual_token_liquidity
+///
+/// The rounding is applied to the exact ratio, so a price sitting just below a tick
+/// boundary is not floored before it is rounded. Halves round up, and a `tick_size`
+/// of zero returns the price rounded down to a whole lamport.
+pub fn price_rounded_to_tick(virtual_sol_liquidity: u64, virtual_token_liquidity: u64, decimals: u8, tick_size: u64) -> Result<u64> {
+    let numerator = virtual_sol_liquidity as u128 * 10u128.checked_pow(decimals as u32).ok_or(MiniPumpError::ArithmeticOverflow)?;
+    let denominator = virtual_token_liquidity as u128;
+    require!(denominator > 0, MiniPumpError::CalculationError);
+
+    let price = if tick_size == 0 {
+        numerator / denominator
+    } else {
+        let tick = denominator * tick_size as u128;
+        (numerator + tick / 2) / tick * tick_size as u128
+    };
+
+    u64::try_from(price).map_err(|_| error!(MiniPumpError::ArithmeticOverflow))
+}
+
+/// Returns how far the spot price moves between two reserve states, in basis points
+///
+/// impact = |price_after - price_before| * 10_000 / price_before, with price = vsol / vtoken,
+/// cross-multiplied so no intermediate price is rounded. Rounded down, so trades too small
+/// to move the price by a full basis point report zero.
+pub fn price_impact_bps(virtual_sol_before: u64, virtual_token_before: u64, virtual_sol_after: u64, virtual_token_after: u64) -> Result<u64> {
+    let before = virtual_sol_before as u128 * virtual_token_after as u128;
+    let after = virtual_sol_after as u128 * virtual_token_before as u128;
+    require!(before > 0, MiniPumpError::CalculationError);
+
+    let impact = after.abs_diff(before).checked_mul(10_000).ok_or(MiniPumpError::ArithmeticOverflow)? / before;
+
+    u64::try_from(impact).map_err(|_| error!(MiniPumpError::ArithmeticOverflow))
+}
+
+/// Returns the constant product k = virtual_sol_liquidity * virtual_token_liquidity
+///
+/// Computed in u128 since the product of two u64 reserves does not fit in a u64.
+pub fn constant_product(virtual_sol_liquidity: u64, virtual_token_liquidity: u64) -> u128 {
+    virtual_sol_liquidity as u128 * virtual_token_liquidity as u128
+}
+
+/// Checks that a trade did not let the curve's constant product k fall beyond `k_tolerance_bps`
+///
+/// Exact k preservation is impossible with integer math: the new reserve k / (x + dx)
+/// rarely divides evenly, so the result has to be rounded one way or the other. The
+/// trading formulas round towards the curve, which makes k creep up by at most one unit
+/// of the other reserve per trade, and the clamp at the sell limit raises it further.
+/// Neither is a leak, so only a fall in k is checked, with the tolerance covering
+/// rounding should a future formula round the other way.
+pub fn assert_curve_invariants(k_before: u128, k_after: u128, k_tolerance_bps: u16) -> Result<()> {
+    let tolerance = k_before * k_tolerance_bps as u128 / 10_000;
+    require!(k_after >= k_before - tolerance, MiniPumpError::CurveInvariantViolated);
+
+    Ok(())
+}
+
+/// Checks that an escrow holding `escrow_lamports` can pay out `outflow` and stay rent-exempt
+///
+/// Only the lamports above `rent_exempt_minimum` are available, so a payout that would
+/// dip into the rent floor fails here rather than mid-transfer.
+pub fn check_escrow_covers(escrow_lamports: u64, rent_exempt_minimum: u64, outflow: u64) -> Result<()> {
+    let available = escrow_lamports.saturating_sub(rent_exempt_minimum);
+    require!(available >= outflow, MiniPumpError::InsufficientSolBalance);
+
+    Ok(())
+}
+
+/// Converts a whole-token amount into base units for a mint with the given decimals
+///
+/// raw_amount = whole_tokens * 10^decimals, failing instead of wrapping when the
+/// result does not fit in a u64.
+pub fn raw_token_amount(whole_tokens: u64, decimals: u8) -> Result<u64> {
+    10u64
+        .checked_pow(decimals as u32)
+        .and_then(|scale| whole_tokens.checked_mul(scale))
+        .ok_or(error!(MiniPumpError::ArithmeticOverflow))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_never_returns_more_than_paid() {
+        let (virtual_sol_liquidity, virtual_token_liquidity) = (1_000_000, 1_000_000_000);
+
+        for sol_amount in [1, 7, 1_000, 123_457, 1_000_000] {
+            let token_out = calculate_token_for_sol(virtual_sol_liquidity, virtual_token_liquidity, sol_amount).unwrap();
+            let sol_back = calculate_sol_for_token(virtual_sol_liquidity + sol_amount, virtual_token_liquidity - token_out, token_out).unwrap();
+
+            assert!(sol_back <= sol_amount);
+        }
+    }
+
+    #[test]
+    fn trade_formulas_do_not_overflow_on_large_reserves() {
+        // the default reserves alone multiply past u64::MAX
+        assert_eq!(calculate_token_for_sol(30_000_000_000, 1_073_000_000_000, 1_000_000_000).unwrap(), 34_612_903_225);
+        assert_eq!(calculate_sol_for_token(30_000_000_000, 1_073_000_000_000, 34_612_903_225).unwrap(), 937_499_999);
+
+        // doubling a reserve of u64::MAX halves the other side, rounded in the curve's favour
+        assert_eq!(calculate_token_for_sol(u64::MAX, u64::MAX, u64::MAX).unwrap(), u64::MAX / 2);
+        assert_eq!(calculate_sol_for_token(u64::MAX, u64::MAX, u64::MAX).unwrap(), u64::MAX / 2);
+        assert_eq!(calculate_token_for_sol(u64::MAX, u64::MAX, 0).unwrap(), 0);
+
+        assert!(calculate_token_for_sol(0, 1_000, 0).is_err());
+        assert!(calculate_sol_for_token(1_000, 0, 0).is_err());
+    }
+
+    #[test]
+    fn gross_up_for_trade_fee_leaves_the_net_amount() {
+        for fee_bps in [0, 1, 100, 333, 9_999] {
+            for net_amount in [0, 1, 999, 1_000_000, 123_456_789] {
+                let gross = gross_up_for_trade_fee(net_amount, fee_bps).unwrap();
+
+                assert!(gross - calculate_trade_fee(gross, fee_bps).unwrap() >= net_amount);
+                // never more than one lamport above the exact gross amount
+                assert!(gross as u128 * (10_000 - fee_bps as u128) < (net_amount as u128 + 1) * 10_000);
+            }
+        }
+        assert!(gross_up_for_trade_fee(1, 10_000).is_err());
+    }
+
+    #[test]
+    fn compound_fee_charges_the_second_fee_on_the_remainder() {
+        assert_eq!(compound_fee_bps(0, 250), 250);
+        // 10% then 10% of the remaining 90% is 19%
+        assert_eq!(compound_fee_bps(1_000, 1_000), 1_900);
+        assert_eq!(compound_fee_bps(100, 100), 199);
+    }
+
+    #[test]
+    fn spread_compounds_both_sides_and_widens_with_the_sell_fee() {
+        assert_eq!(spread_bps(0, 0, 0), 0);
+        // 1% on each side loses 1.99% over a round trip
+        assert_eq!(spread_bps(100, 0, 100), 199);
+        assert!(spread_bps(100, 50, 300) > spread_bps(100, 50, 200));
+        assert_eq!(spread_bps(0, 0, 10_000), 10_000);
+    }
+
+    #[test]
+    fn virtual_token_for_market_cap_solves_the_starting_price() {
+        let supply = 1_000_000_000_000_000;
+
+        // a 30 SOL reserve at a 30 SOL market cap prices the whole supply at the reserve
+        assert_eq!(virtual_token_for_market_cap(30_000_000_000, supply, 30_000_000_000).unwrap(), supply);
+        // a lower target needs more virtual tokens against the same SOL, rounded down
+        assert_eq!(virtual_token_for_market_cap(30_000_000_000, supply, 20_000_000_000).unwrap(), 1_500_000_000_000_000);
+        assert_eq!(virtual_token_for_market_cap(30_000_000_000, supply, 28_000_000_000).unwrap(), 1_071_428_571_428_571);
+        assert!(virtual_token_for_market_cap(30_000_000_000, supply, 0).is_err());
+    }
+
+    #[test]
+    fn raw_token_amount_scales_by_decimals() {
+        assert_eq!(raw_token_amount(1_000_000_000, 6).unwrap(), 1_000_000_000_000_000);
+        assert_eq!(raw_token_amount(1_000_000_000, 9).unwrap(), 1_000_000_000_000_000_000);
+        assert!(raw_token_amount(1_000_000_000, 12).is_err());
+    }
+
+    #[test]
+    fn sell_penalty_decays_to_zero_over_the_window() {
+        let full = calculate_sell_penalty(1_000_000, 1_000, 100, 0).unwrap();
+        let half = calculate_sell_penalty(1_000_000, 1_000, 100, 50).unwrap();
+
+        assert_eq!(full, 100_000);
+        assert_eq!(half, 50_000);
+        assert_eq!(calculate_sell_penalty(1_000_000, 1_000, 100, 100).unwrap(), 0);
+        assert_eq!(calculate_sell_penalty(1_000_000, 0, 100, 0).unwrap(), 0);
+        assert_eq!(calculate_sell_penalty(1_000_000, 1_000, 0, 0).unwrap(), 0);
+
+        assert_eq!(effective_sell_penalty_bps(1_000, 100, 0), 1_000);
+        assert_eq!(effective_sell_penalty_bps(1_000, 100, 50), 500);
+        assert_eq!(effective_sell_penalty_bps(1_000, 100, 100), 0);
+    }
+
+    #[test]
+    fn price_q64_keeps_sub_lamport_precision() {
+        // 30 SOL against 1.073e12 base units is about 0.028 lamports per base unit
+        assert_eq!(price_q64(30_000_000_000, 1_073_000_000_000).unwrap(), 515_752_397_214_619_336);
+        // one lamport against a full 1e15 supply still resolves to a non-zero price
+        assert_eq!(price_q64(1, 1_000_000_000_000_000).unwrap(), 18_446);
+        // whole-lamport prices land in the upper 64 bits
+        assert_eq!(price_q64(10, 5).unwrap(), 2u128 << 64);
+        assert!(price_q64(1, 0).is_err());
+    }
+
+    #[test]
+    fn k_drift_stays_within_tolerance_over_many_trades() {
+        let (mut virtual_sol_liquidity, mut virtual_token_liquidity) = (30_000_000u64, 1_073_000_000u64);
+        let k_start = constant_product(virtual_sol_liquidity, virtual_token_liquidity);
+
+        for sol_amount in [1, 999, 12_345, 250_000, 3, 77_777, 1_000_000, 42] {
+            let k_before = constant_product(virtual_sol_liquidity, virtual_token_liquidity);
+            let token_out = calculate_token_for_sol(virtual_sol_liquidity, virtual_token_liquidity, sol_amount).unwrap();
+            virtual_sol_liquidity += sol_amount;
+            virtual_token_liquidity -= token_out;
+            assert_curve_invariants(k_before, constant_product(virtual_sol_liquidity, virtual_token_liquidity), 1).unwrap();
+
+            let k_before = constant_product(virtual_sol_liquidity, virtual_token_liquidity);
+            let token_amount = token_out / 2;
+            let sol_out = calculate_sol_for_token(virtual_sol_liquidity, virtual_token_liquidity, token_amount).unwrap();
+            virtual_sol_liquidity -= sol_out;
+            virtual_token_liquidity += token_amount;
+            assert_curve_invariants(k_before, constant_product(virtual_sol_liquidity, virtual_token_liquidity), 1).unwrap();
+        }
+
+        // k only creeps up, and by far less than one basis point overall
+        let k_end = constant_product(virtual_sol_liquidity, virtual_token_liquidity);
+        assert!(k_end >= k_start);
+        assert!(k_end - k_start <= k_start / 10_000);
+    }
+
+    #[test]
+    fn assert_curve_invariants_rejects_a_fall_beyond_tolerance() {
+        assert!(assert_curve_invariants(10_000, 9_999, 1).is_ok());
+        assert!(assert_curve_invariants(10_000, 9_998, 1).is_err());
+        assert!(assert_curve_invariants(10_000, 10_500, 0).is_ok());
+    }
+
+    #[test]
+    fn escrow_covers_only_what_sits_above_rent() {
+        assert!(check_escrow_covers(1_500, 1_000, 500).is_ok());
+        assert!(check_escrow_covers(1_500, 1_000, 501).is_err());
+        // an escrow below its rent floor can pay nothing
+        assert!(check_escrow_covers(900, 1_000, 1).is_err());
+        assert!(check_escrow_covers(900, 1_000, 0).is_ok());
+    }
+
+    #[test]
+    fn price_rounds_to_the_nearest_tick() {
+        // 30 SOL against 1.073e12 base units with 6 decimals is 27_958.99 lamports per token
+        assert_eq!(price_rounded_to_tick(30_000_000_000, 1_073_000_000_000, 6, 0).unwrap(), 27_958);
+        assert_eq!(price_rounded_to_tick(30_000_000_000, 1_073_000_000_000, 6, 25).unwrap(), 27_950);
+        assert_eq!(price_rounded_to_tick(30_000_000_000, 1_073_000_000_000, 6, 100).unwrap(), 28_000);
+        // exactly half a tick rounds up
+        assert_eq!(price_rounded_to_tick(150, 1, 0, 100).unwrap(), 200);
+        assert_eq!(price_rounded_to_tick(149, 1, 0, 100).unwrap(), 100);
+    }
+
+    #[test]
+    fn sol_for_token_out_is_the_cheapest_buy_receiving_it() {
+        let (virtual_sol_liquidity, virtual_token_liquidity) = (1_000_000, 1_000_000_000);
+
+        for growth_bps in [0, 100, 2_500] {
+            for token_out in [1, 999, 1_000_000] {
+                let sol_amount = calculate_sol_for_token_out(virtual_sol_liquidity, virtual_token_liquidity, token_out, growth_bps).unwrap();
+                let received = |sol: u64| {
+                    let growth = calculate_liquidity_growth(sol, growth_bps).unwrap();
+                    calculate_token_for_sol(virtual_sol_liquidity, virtual_token_liquidity, sol - growth).unwrap()
+                };
+
+                assert!(received(sol_amount) >= token_out);
+                if growth_bps == 0 {
+                    assert!(received(sol_amount - 1) < token_out);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn price_impact_is_zero_for_dust_and_symmetric_in_direction() {
+        let (virtual_sol_liquidity, virtual_token_liquidity) = (1_000_000, 1_000_000_000);
+
+        assert_eq!(price_impact_bps(virtual_sol_liquidity, virtual_token_liquidity, virtual_sol_liquidity + 1, virtual_token_liquidity).unwrap(), 0);
+        // doubling the price is a 100% move, halving it a 50% move
+        assert_eq!(price_impact_bps(virtual_sol_liquidity, virtual_token_liquidity, virtual_sol_liquidity * 2, virtual_token_liquidity).unwrap(), 10_000);
+        assert_eq!(price_impact_bps(virtual_sol_liquidity, virtual_token_liquidity, virtual_sol_liquidity, virtual_token_liquidity * 2).unwrap(), 5_000);
+    }
+
+    #[test]
+    fn tokens_for_sol_out_is_the_smallest_sell_paying_it() {
+        let (virtual_sol_liquidity, virtual_token_liquidity) = (1_000_000, 1_000_000_000);
+
+        for growth_bps in [0, 100, 2_500] {
+            for sol_out in [1, 999, 100_000] {
+                let token_amount = calculate_tokens_for_sol_out(virtual_sol_liquidity, virtual_token_liquidity, sol_out, growth_bps).unwrap();
+                let paid = |tokens: u64| {
+                    let sol = calculate_sol_for_token(virtual_sol_liquidity, virtual_token_liquidity, tokens).unwrap();
+                    sol - calculate_liquidity_growth(sol, growth_bps).unwrap()
+                };
+
+                assert!(paid(token_amount) >= sol_out);
+                if growth_bps == 0 {
+                    assert!(paid(token_amount - 1) < sol_out);
+                }
+            }
+        }
+
+        assert!(calculate_tokens_for_sol_out(virtual_sol_liquidity, virtual_token_liquidity, virtual_sol_liquidity, 0).is_err());
+    }
+}
@@ -9,6 +9,32 @@ pub struct BondingCurve {
     pub tokens_sold: u64,
     pub token_mint: Pubkey,
     pub is_active: bool,
+    // tokens the curve's token account must always keep for migration liquidity
+    pub migration_reserve: u64,
+    // the wallet that launched the curve
+    pub creator: Pubkey,
+    // unix timestamp of the launch
+    pub created_at: i64,
+    // sequential launch number across the protocol, starting at zero
+    pub launch_id: u64,
+    // set once withdraw_funds has moved the curve's assets out for migration
+    pub migrated: bool,
+    // number of buys and sells since launch
+    pub trade_count: u64,
+    // SOL traded across all buys and sells, priced on the curve before any share is withheld
+    pub total_volume_sol: u64,
+    // SOL buyers paid in minus SOL sellers were paid out
+    pub total_sol_raised: u64,
+    // unix timestamp of the latest buy or sell, zero until the first trade
+    pub last_trade_timestamp: i64,
+    // the global config epoch the curve launched under
+    pub config_epoch: u64,
+    // highest tokens_sold ever reached, which sells do not lower
+    pub max_tokens_sold: u64,
+    // tokens minted to the curve at launch, always tokens_to_sell + migration_reserve
+    pub total_supply: u64,
+    // tokens the curve sells before graduating
+    pub tokens_to_sell: u64,
     pub bump: u8,
 }
 
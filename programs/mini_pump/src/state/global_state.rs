@@ -1,16 +1,127 @@
 use anchor_lang::prelude::*;
 
+use crate::constants::MAX_FEE_RECIPIENTS;
+use crate::errors::MiniPumpError;
+
 #[account]
 #[derive(InitSpace)]
 pub struct GlobalState {
     pub owner: Pubkey,
-    pub tokens_to_sell: Pubkey, 
+    /// Tokens (in base units) each new curve sells before graduating
+    pub tokens_to_sell: u64,
+    /// Tokens (in base units) minted to each new curve, the rest beyond `tokens_to_sell` kept for migration
     pub total_tokens_to_mint: u64,
     pub virtual_sol_liquidity: u64,
     pub virtual_token_liquidity: u64,
+    /// Minimum tokens a single buy must purchase at the current price, zero disables it
+    pub min_tokens_per_buy: u64,
+    /// Whether launches must claim their symbol in the symbol registry
+    pub enforce_unique_symbols: bool,
+    /// SOL charged to the launcher and sent to the treasury on every launch
+    pub launch_fee: u64,
+    /// Maximum metadata uri length accepted at launch, zero falls back to the Metaplex limit
+    pub max_uri_len: u16,
+    /// Extra SOL paid from the treasury to the migrator when a curve's funds are withdrawn
+    pub graduation_bonus_sol: u64,
+    /// Trades moving more SOL than this also emit a WhaleTrade event, zero disables it
+    pub whale_threshold_sol: u64,
+    /// Maximum whole-token supply a single launch may mint, zero disables it
+    pub max_total_supply_per_launch: u64,
+    /// Floor a buy may not push a curve's virtual token liquidity below, zero disables it
+    pub min_virtual_token_liquidity: u64,
+    /// Penalty withheld from a sell right after the wallet's last buy, in basis points of the proceeds
+    pub sell_penalty_bps: u16,
+    /// Seconds over which the sell penalty decays linearly to zero
+    pub sell_penalty_window: i64,
+    /// Largest share of the curve's unsold tokens one buy may take, in basis points, zero disables it
+    pub max_buy_fraction_bps: u16,
+    /// How far a trade may let the curve's k fall, in basis points
+    pub k_tolerance_bps: u16,
+    /// Number of curves launched so far, used to number each launch
+    pub launch_count: u64,
+    /// SOL the escrow must hold above rent before the owner can deactivate a curve manually
+    pub min_graduation_sol: u64,
+    /// Whether buys accumulate SOL spent and tokens bought on the trader position
+    pub track_entry_prices: bool,
+    /// Share of each trade's SOL kept in the virtual SOL reserve instead of trading, in basis points
+    pub liquidity_growth_bps: u16,
+    /// Minimum seconds between two owner config changes
+    pub config_change_cooldown: i64,
+    /// Unix timestamp of the last owner config change
+    pub last_config_change: i64,
+    /// Counter bumped whenever the default virtual reserves for new curves change
+    pub config_epoch: u64,
+    /// Key governance intends to hand the program upgrade authority to, recorded for transparency only
+    pub upgrade_authority_target: Pubkey,
+    /// Lamports a buyer must keep beyond the purchase for a buy to be accepted
+    pub min_buyer_lamports: u64,
+    /// Which trade event every trade emits, see the `EVENT_VERBOSITY_*` constants
+    pub event_verbosity: u8,
+    /// Whether new launches are rejected while existing curves keep trading
+    pub launches_paused: bool,
+    /// Maximum number of curves trading at once, zero disables it
+    pub max_active_curves: u64,
+    /// Number of launched curves that have not graduated yet
+    pub active_curve_count: u64,
+    /// Fee taken from the SOL leg of every trade into the fee vault, in basis points
+    pub fee_basis_points: u16,
+    /// Whether all trading is halted, launches and withdrawals stay open
+    pub is_paused: bool,
+    /// Key proposed as the next owner, the default key while no transfer is pending
+    pub pending_owner: Pubkey,
+    /// Wallets `collect_fees` splits the trade fees between, unused slots have a zero share
+    pub fee_recipients: [FeeRecipient; MAX_FEE_RECIPIENTS as usize],
+    /// Whether buys pay the trade fee in tokens kept by the curve instead of in SOL
+    pub fee_in_token: bool,
     pub bump: u8,
 }
 
+/// A wallet receiving a share of the collected trade fees
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, InitSpace)]
+pub struct FeeRecipient {
+    /// The wallet paid the share
+    pub recipient: Pubkey,
+    /// Share of each collection, in basis points
+    pub share_bps: u16,
+}
+
+impl GlobalState {
+    /// Checks a launch supply split, which must sell some tokens and keep some for migration
+    ///
+    /// The sale must also stay below `virtual_token_liquidity`, since buys can never take
+    /// the curve's virtual token reserve to zero and a larger sale could not complete.
+    pub fn validate_supply_split(total_tokens_to_mint: u64, tokens_to_sell: u64, virtual_token_liquidity: u64) -> Result<()> {
+        require!(tokens_to_sell > 0 && tokens_to_sell < total_tokens_to_mint, MiniPumpError::SupplyReconciliationError);
+        require!(tokens_to_sell < virtual_token_liquidity, MiniPumpError::SupplyExceedsLiquidity);
+
+        Ok(())
+    }
+
+    /// Checks a trade fee, which must leave part of every trade's SOL once taken
+    pub fn validate_fee_basis_points(fee_basis_points: u16) -> Result<()> {
+        require!(fee_basis_points < 10_000, MiniPumpError::InvalidBasisPoints);
 
-// token_to_sell will be 800 million
-// total tokens to mint will be 1 billion - remaining 200 will go to the migrator to create the lqiudity on the dex. 
+        Ok(())
+    }
+
+    /// The trade fee a buy pays, in basis points of its SOL and of the tokens it buys
+    ///
+    /// Only one of the two is set, depending on `fee_in_token`.
+    pub fn buy_fee_bps(&self) -> (u16, u16) {
+        if self.fee_in_token {
+            (0, self.fee_basis_points)
+        } else {
+            (self.fee_basis_points, 0)
+        }
+    }
+
+    /// The configured fee recipients, empty when the owner collects the fees alone
+    pub fn active_fee_recipients(&self) -> impl Iterator<Item = &FeeRecipient> {
+        self.fee_recipients.iter().filter(|fee_recipient| fee_recipient.share_bps > 0)
+    }
+
+    /// Tokens (in base units) each new curve keeps for the migrator to create the dex liquidity
+    pub fn migration_reserve(&self) -> Result<u64> {
+        self.total_tokens_to_mint.checked_sub(self.tokens_to_sell).ok_or(error!(MiniPumpError::SupplyReconciliationError))
+    }
+}
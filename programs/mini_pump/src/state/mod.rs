@@ -1,6 +1,10 @@
 pub mod bonding_curve;
 pub mod global_state;
+pub mod symbol_registry;
+pub mod trader_position;
 
 
 pub use bonding_curve::*;
 pub use global_state::*;
+pub use symbol_registry::*;
+pub use trader_position::*;
@@ -0,0 +1,11 @@
+use anchor_lang::prelude::*;
+
+#[account]
+#[derive(InitSpace)]
+pub struct SymbolRegistry {
+    // the mint that claimed the symbol, default until a launch populates it
+    pub token_mint: Pubkey,
+    pub bump: u8,
+}
+
+// one registry entry per symbol, keyed by the sha256 hash of the exact symbol bytes
@@ -0,0 +1,15 @@
+use anchor_lang::prelude::*;
+
+#[account]
+#[derive(InitSpace)]
+pub struct TraderPosition {
+    // unix timestamp of the wallet's most recent buy on the curve
+    pub last_buy_at: i64,
+    // lamports spent on buys while entry tracking was enabled
+    pub sol_spent: u64,
+    // token base units received from those buys
+    pub tokens_bought: u64,
+    pub bump: u8,
+}
+
+// one position per wallet per curve, only needed while the sell penalty or entry tracking is enabled
@@ -1,16 +1,2721 @@
 import * as anchor from "@coral-xyz/anchor";
-import { Program } from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { assert } from "chai";
 import { MiniPump } from "../target/types/mini_pump";
 
+const { Keypair, PublicKey, SystemProgram, LAMPORTS_PER_SOL } = anchor.web3;
+const { TOKEN_PROGRAM_ID, ASSOCIATED_PROGRAM_ID } = anchor.utils.token;
+
+const METADATA_PROGRAM_ID = new PublicKey(
+  "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s"
+);
+
+// both in base units, the rest of the supply is kept for migration
+const TOTAL_TOKENS_TO_MINT = new BN("1000000000000000");
+const TOKENS_TO_SELL = new BN("800000000000");
+const FEE_BASIS_POINTS = 0;
+const VIRTUAL_SOL_LIQUIDITY = new BN(30 * LAMPORTS_PER_SOL);
+const VIRTUAL_TOKEN_LIQUIDITY = new BN("1073000000000");
+const NO_MAX_PRICE = new BN(0);
+const NO_MIN_SOL_OUT = new BN(0);
+
+interface Curve {
+  mint: anchor.web3.PublicKey;
+  bondingCurve: anchor.web3.PublicKey;
+  solEscrow: anchor.web3.PublicKey;
+  curveTokenAccount: anchor.web3.PublicKey;
+  metadata: anchor.web3.PublicKey;
+}
+
+const sol = (amount: number) => new BN(amount * LAMPORTS_PER_SOL);
+
+async function expectError(promise: Promise<unknown>, code: string) {
+  let error: unknown;
+  try {
+    await promise;
+  } catch (err) {
+    error = err;
+  }
+  assert.ok(error, `expected ${code}`);
+  assert.include(String(error), code);
+}
+
 describe("mini_pump", () => {
   // Configure the client to use the local cluster.
-  anchor.setProvider(anchor.AnchorProvider.env());
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
 
   const program = anchor.workspace.MiniPump as Program<MiniPump>;
+  const wallet = provider.wallet.publicKey;
+
+  const [globalState] = PublicKey.findProgramAddressSync(
+    [Buffer.from("global_state")],
+    program.programId
+  );
+  const [treasury] = PublicKey.findProgramAddressSync(
+    [Buffer.from("treasury")],
+    program.programId
+  );
+  const [feeVault] = PublicKey.findProgramAddressSync(
+    [Buffer.from("fee_vault")],
+    program.programId
+  );
+
+  async function eventsOf(signature: string) {
+    await provider.connection.confirmTransaction(signature, "confirmed");
+    const tx = await provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const parser = new anchor.EventParser(program.programId, program.coder);
+    return Array.from(parser.parseLogs(tx.meta.logMessages));
+  }
+
+  function curvePdas(mint: anchor.web3.PublicKey): Curve {
+    const [bondingCurve] = PublicKey.findProgramAddressSync(
+      [Buffer.from("bonding_curve"), mint.toBuffer()],
+      program.programId
+    );
+    const [solEscrow] = PublicKey.findProgramAddressSync(
+      [Buffer.from("bonding_curve_sol_escrow"), bondingCurve.toBuffer()],
+      program.programId
+    );
+    const curveTokenAccount = anchor.utils.token.associatedAddress({
+      mint,
+      owner: bondingCurve,
+    });
+    const [metadata] = PublicKey.findProgramAddressSync(
+      [Buffer.from("metadata"), METADATA_PROGRAM_ID.toBuffer(), mint.toBuffer()],
+      METADATA_PROGRAM_ID
+    );
+    return { mint, bondingCurve, solEscrow, curveTokenAccount, metadata };
+  }
+
+  function symbolRegistryPda(symbol: string) {
+    const [symbolRegistry] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("symbol_registry"),
+        Buffer.from(anchor.utils.sha256.hash(symbol), "hex"),
+      ],
+      program.programId
+    );
+    return symbolRegistry;
+  }
+
+  interface LaunchOptions {
+    name?: string;
+    symbol?: string;
+    uri?: string;
+    claimSymbol?: boolean;
+  }
+
+  async function launchCoin(opts: LaunchOptions = {}): Promise<Curve> {
+    const symbol = opts.symbol ?? "MPT";
+    const mint = Keypair.generate();
+    const curve = curvePdas(mint.publicKey);
+    await program.methods
+      .launchCoin(
+        opts.name ?? "Mini Pump Token",
+        symbol,
+        opts.uri ?? "https://example.com/mpt.json"
+      )
+      .accountsPartial({
+        payer: wallet,
+        globalState,
+        bondingCurve: curve.bondingCurve,
+        bondingCurveSolEscrow: curve.solEscrow,
+        tokenMint: mint.publicKey,
+        metadata: curve.metadata,
+        bondingCurveTokenAccount: curve.curveTokenAccount,
+        symbolRegistry: opts.claimSymbol ? symbolRegistryPda(symbol) : null,
+        treasury,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        tokenMetadataProgram: METADATA_PROGRAM_ID,
+      })
+      .signers([mint])
+      .rpc();
+    return curve;
+  }
+
+  function tradeAccounts(curve: Curve, buyer: anchor.web3.PublicKey) {
+    return {
+      buyer,
+      buyerTokenAccount: anchor.utils.token.associatedAddress({
+        mint: curve.mint,
+        owner: buyer,
+      }),
+      traderPosition: null,
+      solEscrow: curve.solEscrow,
+      bondingCurve: curve.bondingCurve,
+      bondingCurveTokenAccount: curve.curveTokenAccount,
+      globalState,
+      feeVault,
+      tokenMint: curve.mint,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    };
+  }
+
+  // TradeCoin accounts in declaration order, for instructions taking them as remaining accounts
+  function tradeAccountMetas(curve: Curve, buyer: anchor.web3.PublicKey) {
+    const accounts = tradeAccounts(curve, buyer);
+    const writable = (pubkey: anchor.web3.PublicKey) => ({
+      pubkey,
+      isSigner: false,
+      isWritable: true,
+    });
+    const readonly = (pubkey: anchor.web3.PublicKey) => ({
+      pubkey,
+      isSigner: false,
+      isWritable: false,
+    });
+    return [
+      { pubkey: buyer, isSigner: true, isWritable: true },
+      writable(accounts.buyerTokenAccount),
+      // omitted optional trader position
+      readonly(program.programId),
+      writable(curve.solEscrow),
+      writable(curve.bondingCurve),
+      writable(curve.curveTokenAccount),
+      writable(globalState),
+      writable(feeVault),
+      readonly(curve.mint),
+      readonly(TOKEN_PROGRAM_ID),
+      readonly(ASSOCIATED_PROGRAM_ID),
+      readonly(SystemProgram.programId),
+    ];
+  }
+
+  function traderPositionPda(curve: Curve, trader: anchor.web3.PublicKey) {
+    const [traderPosition] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("trader_position"),
+        curve.bondingCurve.toBuffer(),
+        trader.toBuffer(),
+      ],
+      program.programId
+    );
+    return traderPosition;
+  }
+
+  async function tokenBalance(curve: Curve, owner = wallet): Promise<BN> {
+    const balance = await provider.connection.getTokenAccountBalance(
+      anchor.utils.token.associatedAddress({ mint: curve.mint, owner })
+    );
+    return new BN(balance.value.amount);
+  }
+
+  function queryAccounts(curve: Curve) {
+    return { bondingCurve: curve.bondingCurve, tokenMint: curve.mint };
+  }
+
+  function withdrawQueryAccounts(curve: Curve) {
+    return {
+      ...queryAccounts(curve),
+      solEscrow: curve.solEscrow,
+      bondingCurveTokenAccount: curve.curveTokenAccount,
+    };
+  }
+
+  // withdraw_funds and graduate_and_withdraw accounts, paying the wallet as the owner
+  function withdrawAccounts(curve: Curve) {
+    return {
+      ...withdrawQueryAccounts(curve),
+      payer: wallet,
+      globalState,
+      payerTokenAccount: tradeAccounts(curve, wallet).buyerTokenAccount,
+      treasury,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    };
+  }
+
+  function createBuyerAta(curve: Curve, buyer = wallet) {
+    return program.methods.createBuyerAta().accountsPartial({
+      buyer,
+      buyerTokenAccount: tradeAccounts(curve, buyer).buyerTokenAccount,
+      bondingCurve: curve.bondingCurve,
+      tokenMint: curve.mint,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    });
+  }
+
+  // creates the wallet's token account first, which is a no-op once it exists
+  async function buy(
+    curve: Curve,
+    solAmount: BN,
+    maxPrice = NO_MAX_PRICE,
+    expectedEpoch: BN | null = null
+  ) {
+    return program.methods
+      .buyToken(solAmount, maxPrice, expectedEpoch, null)
+      .accountsPartial(tradeAccounts(curve, wallet))
+      .preInstructions([await createBuyerAta(curve).instruction()])
+      .rpc();
+  }
+
+  before(async () => {
+    await program.methods
+      .initProtocol(
+        TOTAL_TOKENS_TO_MINT,
+        VIRTUAL_SOL_LIQUIDITY,
+        VIRTUAL_TOKEN_LIQUIDITY,
+        TOKENS_TO_SELL,
+        FEE_BASIS_POINTS
+      )
+      .accountsPartial({ payer: wallet, globalState, treasury, feeVault })
+      .rpc();
+  });
+
+  it("suggests virtual reserves matching a target market cap", async () => {
+    const targetMcap = new BN(20 * LAMPORTS_PER_SOL);
+    const supply = new BN("1000000000000000");
+
+    const reserves = await program.methods
+      .computeReservesForMarketCap(targetMcap, supply)
+      .view();
+
+    // the SOL side stays at 30 SOL and the token side is solved for the target
+    assert.ok(reserves.virtualSolLiquidity.eq(new BN(30 * LAMPORTS_PER_SOL)));
+    assert.ok(reserves.virtualTokenLiquidity.eq(new BN("1500000000000000")));
+
+    // starting price (vsol / vtoken) times supply should land on the target
+    const startingMcap = reserves.virtualSolLiquidity
+      .mul(supply)
+      .div(reserves.virtualTokenLiquidity);
+    assert.ok(startingMcap.eq(targetMcap));
+  });
+
+  it("enforces the token floor on buys as the price rises", async () => {
+    const curve = await launchCoin();
+    await program.methods
+      .setMinTokensPerBuy(new BN("25000000000"))
+      .accountsPartial({ owner: wallet, globalState })
+      .rpc();
+
+    // at the starting price 1 SOL clears the floor
+    await buy(curve, sol(1));
+
+    // after a large buy the same SOL amount purchases too few tokens
+    await buy(curve, sol(10));
+    await expectError(buy(curve, sol(1)), "BuyTooSmall");
+
+    await program.methods
+      .setMinTokensPerBuy(new BN(0))
+      .accountsPartial({ owner: wallet, globalState })
+      .rpc();
+  });
+
+  it("moves ownership only once the proposed owner accepts", async () => {
+    const newOwner = Keypair.generate();
+    const transferOwnership = (to: anchor.web3.PublicKey, owner = wallet) =>
+      program.methods
+        .transferOwnership(to)
+        .accountsPartial({ owner, globalState });
+    const acceptOwnership = (signer: anchor.web3.Keypair) =>
+      program.methods
+        .acceptOwnership()
+        .accountsPartial({ newOwner: signer.publicKey, globalState })
+        .signers([signer])
+        .rpc();
+
+    await expectError(transferOwnership(PublicKey.default).rpc(), "InvalidNewOwner");
+
+    // proposing leaves the current owner in charge
+    await transferOwnership(newOwner.publicKey).rpc();
+    let state = await program.account.globalState.fetch(globalState);
+    assert.ok(state.owner.equals(wallet));
+    assert.ok(state.pendingOwner.equals(newOwner.publicKey));
+    await expectError(acceptOwnership(Keypair.generate()), "NotPendingOwner");
+
+    const signature = await acceptOwnership(newOwner);
+    const event = (await eventsOf(signature)).find(
+      (e) => e.name === "ownershipTransferred"
+    );
+    assert.ok(event);
+    assert.ok(event.data.oldOwner.equals(wallet));
+    assert.ok(event.data.newOwner.equals(newOwner.publicKey));
+    state = await program.account.globalState.fetch(globalState);
+    assert.ok(state.owner.equals(newOwner.publicKey));
+    assert.ok(state.pendingOwner.equals(PublicKey.default));
+
+    // hand ownership back so later tests can keep administering the protocol
+    await transferOwnership(wallet, newOwner.publicKey)
+      .signers([newOwner])
+      .rpc();
+    await program.methods
+      .acceptOwnership()
+      .accountsPartial({ newOwner: wallet, globalState })
+      .rpc();
+  });
+
+  it("rejects a second launch reusing a registered symbol", async () => {
+    await program.methods
+      .setEnforceUniqueSymbols(true)
+      .accountsPartial({ owner: wallet, globalState })
+      .rpc();
+
+    await launchCoin({ symbol: "UNIQ", claimSymbol: true });
+    await expectError(
+      launchCoin({ symbol: "UNIQ", claimSymbol: true }),
+      "SymbolTaken"
+    );
+
+    await program.methods
+      .setEnforceUniqueSymbols(false)
+      .accountsPartial({ owner: wallet, globalState })
+      .rpc();
+  });
+
+  it("charges the launch fee to the treasury", async () => {
+    const launchFee = sol(0.05);
+    await program.methods
+      .setLaunchFee(launchFee)
+      .accountsPartial({ owner: wallet, globalState })
+      .rpc();
+
+    const before = await provider.connection.getBalance(treasury);
+    await launchCoin();
+    const after = await provider.connection.getBalance(treasury);
+    assert.equal(after - before, launchFee.toNumber());
+
+    await program.methods
+      .setLaunchFee(new BN(0))
+      .accountsPartial({ owner: wallet, globalState })
+      .rpc();
+  });
+
+  it("lets only the owner withdraw the treasury above its rent floor", async () => {
+    const withdrawTreasury = (amount: BN, owner = wallet) =>
+      program.methods
+        .withdrawTreasury(amount)
+        .accountsPartial({ owner, globalState, treasury });
+    await program.methods
+      .setLaunchFee(sol(0.05))
+      .accountsPartial({ owner: wallet, globalState })
+      .rpc();
+    await launchCoin();
+    await program.methods
+      .setLaunchFee(new BN(0))
+      .accountsPartial({ owner: wallet, globalState })
+      .rpc();
+
+    const stranger = Keypair.generate();
+    await expectError(
+      withdrawTreasury(sol(0.01), stranger.publicKey).signers([stranger]).rpc(),
+      "NotOwner"
+    );
+
+    const rentExempt =
+      await provider.connection.getMinimumBalanceForRentExemption(0);
+    const available = (await provider.connection.getBalance(treasury)) - rentExempt;
+    await expectError(
+      withdrawTreasury(new BN(available + 1)).rpc(),
+      "InsufficientSolBalance"
+    );
+
+    await withdrawTreasury(new BN(available)).rpc();
+    assert.equal(await provider.connection.getBalance(treasury), rentExempt);
+  });
+
+  it("reports fewer tokens remaining as buys occur", async () => {
+    const curve = await launchCoin();
+    const remaining = () =>
+      program.methods
+        .getTokensRemaining()
+        .accountsPartial(queryAccounts(curve))
+        .view();
+
+    const before: BN = await remaining();
+    await buy(curve, sol(1));
+    const after: BN = await remaining();
+
+    const { tokensSold } = await program.account.bondingCurve.fetch(
+      curve.bondingCurve
+    );
+    assert.ok(after.lt(before));
+    assert.ok(before.sub(after).eq(tokensSold));
+  });
+
+  it("creates token metadata at the Metaplex metadata PDA", async () => {
+    const curve = await launchCoin({ symbol: "META" });
+
+    const info = await provider.connection.getAccountInfo(curve.metadata);
+    assert.ok(info.owner.equals(METADATA_PROGRAM_ID));
+
+    // layout: key (1) | update authority (32) | mint (32) | name | symbol | ...
+    const mint = new PublicKey(info.data.subarray(33, 65));
+    assert.ok(mint.equals(curve.mint));
+
+    const nameLen = info.data.readUInt32LE(65);
+    const name = info.data
+      .subarray(69, 69 + nameLen)
+      .toString()
+      .replace(/\0/g, "");
+    const symbolLen = info.data.readUInt32LE(69 + nameLen);
+    const symbol = info.data
+      .subarray(73 + nameLen, 73 + nameLen + symbolLen)
+      .toString()
+      .replace(/\0/g, "");
+    assert.equal(name, "Mini Pump Token");
+    assert.equal(symbol, "META");
+  });
+
+  it("enforces the configured maximum uri length", async () => {
+    await program.methods
+      .setMaxUriLen(32)
+      .accountsPartial({ owner: wallet, globalState })
+      .rpc();
+
+    await launchCoin({ uri: "x".repeat(32) });
+    await expectError(launchCoin({ uri: "x".repeat(33) }), "UriTooLong");
+
+    // zero falls back to the Metaplex limit
+    await program.methods
+      .setMaxUriLen(0)
+      .accountsPartial({ owner: wallet, globalState })
+      .rpc();
+    await launchCoin({ uri: "x".repeat(200) });
+    await expectError(launchCoin({ uri: "x".repeat(201) }), "UriTooLong");
+  });
+
+  it("quotes a buy-then-sell round trip at no more than the input", async () => {
+    const curve = await launchCoin();
+    const solAmount = sol(1);
+
+    const returned: BN = await program.methods
+      .quoteRoundTrip(solAmount)
+      .accountsPartial(queryAccounts(curve))
+      .view();
+
+    // with no trading fee the only spread is integer rounding, which favours the curve
+    assert.ok(returned.lt(solAmount));
+    assert.ok(solAmount.sub(returned).lten(2));
+
+    // a round trip whose buy buy_token would reject is rejected the same way
+    const setMinTokensPerBuy = (min: BN) =>
+      program.methods
+        .setMinTokensPerBuy(min)
+        .accountsPartial({ owner: wallet, globalState })
+        .rpc();
+    await setMinTokensPerBuy(VIRTUAL_TOKEN_LIQUIDITY);
+    await expectError(
+      program.methods
+        .quoteRoundTrip(solAmount)
+        .accountsPartial(queryAccounts(curve))
+        .view(),
+      "BuyTooSmall"
+    );
+    await setMinTokensPerBuy(new BN(0));
+  });
+
+  it("keeps the curve token balance at its floor when the cap is bought out", async () => {
+    const curve = await launchCoin();
+
+    // large enough to hit the sell limit in one buy
+    await buy(curve, sol(100));
+
+    const { migrationReserve, isActive } =
+      await program.account.bondingCurve.fetch(curve.bondingCurve);
+    const balance = await provider.connection.getTokenAccountBalance(
+      curve.curveTokenAccount
+    );
+    assert.isFalse(isActive);
+    assert.equal(balance.value.amount, migrationReserve.toString());
+  });
+
+  it("sells positions across several curves in one basket", async () => {
+    const curves = [await launchCoin(), await launchCoin()];
+    for (const curve of curves) {
+      await buy(curve, sol(1));
+    }
+    const amounts = await Promise.all(curves.map((c) => tokenBalance(c)));
+
+    await program.methods
+      .sellBasket(amounts, amounts.map(() => NO_MIN_SOL_OUT))
+      .accountsPartial({ seller: wallet })
+      .remainingAccounts(curves.flatMap((c) => tradeAccountMetas(c, wallet)))
+      .rpc();
+
+    for (const curve of curves) {
+      assert.ok((await tokenBalance(curve)).isZero());
+    }
+  });
+
+  it("rolls back the whole basket when one sell fails", async () => {
+    const curves = [await launchCoin(), await launchCoin()];
+    for (const curve of curves) {
+      await buy(curve, sol(1));
+    }
+    const amounts = await Promise.all(curves.map((c) => tokenBalance(c)));
+
+    // the second sell asks for more tokens than the wallet holds
+    await expectError(
+      program.methods
+        .sellBasket(
+          [amounts[0], amounts[1].addn(1)],
+          amounts.map(() => NO_MIN_SOL_OUT)
+        )
+        .accountsPartial({ seller: wallet })
+        .remainingAccounts(curves.flatMap((c) => tradeAccountMetas(c, wallet)))
+        .rpc(),
+      // spl-token InsufficientFunds
+      "custom program error: 0x1"
+    );
+
+    assert.ok((await tokenBalance(curves[0])).eq(amounts[0]));
+    assert.ok((await tokenBalance(curves[1])).eq(amounts[1]));
+  });
+
+  it("rolls back the whole basket when one sell misses its min_sol_out", async () => {
+    const curves = [await launchCoin(), await launchCoin()];
+    for (const curve of curves) {
+      await buy(curve, sol(1));
+    }
+    const amounts = await Promise.all(curves.map((c) => tokenBalance(c)));
+    const sellBasket = (minSolOuts: BN[]) =>
+      program.methods
+        .sellBasket(amounts, minSolOuts)
+        .accountsPartial({ seller: wallet })
+        .remainingAccounts(curves.flatMap((c) => tradeAccountMetas(c, wallet)))
+        .rpc();
+
+    // one minimum per sell
+    await expectError(sellBasket([NO_MIN_SOL_OUT]), "InvalidTokenAmount");
+    // more than the second escrow could ever pay for its tokens
+    await expectError(sellBasket([NO_MIN_SOL_OUT, sol(2)]), "SlippageExceeded");
+    assert.ok((await tokenBalance(curves[0])).eq(amounts[0]));
+    assert.ok((await tokenBalance(curves[1])).eq(amounts[1]));
+
+    await sellBasket([sol(0.9), sol(0.9)]);
+    for (const curve of curves) {
+      assert.ok((await tokenBalance(curve)).isZero());
+    }
+  });
+
+  it("rejects a pre-created buyer token account for another mint", async () => {
+    const [curve, other] = [await launchCoin(), await launchCoin()];
+    await buy(other, sol(0.1));
+
+    // the buyer's existing account on the other curve stands in for the ATA
+    await expectError(
+      program.methods
+        .buyToken(sol(0.1), NO_MAX_PRICE, null, null)
+        .accountsPartial({
+          ...tradeAccounts(curve, wallet),
+          buyerTokenAccount: tradeAccounts(other, wallet).buyerTokenAccount,
+        })
+        .rpc(),
+      "ConstraintAssociated"
+    );
+  });
+
+  it("emits WhaleTrade only for trades above the threshold", async () => {
+    await program.methods
+      .setWhaleThresholdSol(sol(1))
+      .accountsPartial({ owner: wallet, globalState })
+      .rpc();
+
+    const curve = await launchCoin();
+    const whales = async (signature: string) =>
+      (await eventsOf(signature)).filter((e) => e.name === "whaleTrade");
+
+    assert.lengthOf(await whales(await buy(curve, sol(0.5))), 0);
+    const [event] = await whales(await buy(curve, sol(2)));
+    assert.ok(event.data.trader.equals(wallet));
+    assert.isTrue(event.data.isBuy);
+    assert.ok(event.data.solAmount.eq(sol(2)));
+
+    await program.methods
+      .setWhaleThresholdSol(new BN(0))
+      .accountsPartial({ owner: wallet, globalState })
+      .rpc();
+  });
+
+  it("leaves the sol escrow rent-exempt right after launch", async () => {
+    const curve = await launchCoin();
+
+    const lamports = await provider.connection.getBalance(curve.solEscrow);
+    const rentExempt =
+      await provider.connection.getMinimumBalanceForRentExemption(0);
+    assert.isAtLeast(lamports, rentExempt);
+  });
+
+  it("pays a full sell-back out of the escrow without touching its rent", async () => {
+    const curve = await launchCoin();
+    await createBuyerAta(curve).rpc();
+    await buy(curve, sol(1));
+
+    await program.methods
+      .sellToken(await tokenBalance(curve), NO_MIN_SOL_OUT, null)
+      .accountsPartial(tradeAccounts(curve, wallet))
+      .rpc();
+
+    assert.ok((await tokenBalance(curve)).isZero());
+    const rentExempt =
+      await provider.connection.getMinimumBalanceForRentExemption(0);
+    assert.isAtLeast(
+      await provider.connection.getBalance(curve.solEscrow),
+      rentExempt
+    );
+  });
+
+  it("rejects launches above the supply ceiling", async () => {
+    const setCeiling = (ceiling: BN) =>
+      program.methods
+        .setMaxTotalSupplyPerLaunch(ceiling)
+        .accountsPartial({ owner: wallet, globalState })
+        .rpc();
+
+    // the ceiling is in whole tokens
+    const wholeSupply = TOTAL_TOKENS_TO_MINT.divn(1_000_000);
+    await setCeiling(wholeSupply.subn(1));
+    await expectError(launchCoin(), "SupplyTooLarge");
+
+    await setCeiling(wholeSupply);
+    await launchCoin();
+
+    await setCeiling(new BN(0));
+  });
+
+  it("roughly preserves k across a trade", async () => {
+    const curve = await launchCoin();
+    const getK = (): Promise<BN> =>
+      program.methods.getK().accountsPartial(queryAccounts(curve)).view();
+
+    const before = await getK();
+    await buy(curve, sol(1));
+    const after = await getK();
+
+    // rounding favours the curve, so k can only creep up by a few reserve units
+    assert.ok(after.gte(before));
+    assert.ok(after.sub(before).lte(VIRTUAL_SOL_LIQUIDITY.muln(2)));
+  });
+
+  it("rejects buys pushing virtual token liquidity below the floor", async () => {
+    const setFloor = (floor: BN) =>
+      program.methods
+        .setMinVirtualTokenLiquidity(floor)
+        .accountsPartial({ owner: wallet, globalState })
+        .rpc();
+
+    // roughly 1.5 SOL of buying room above the floor
+    await setFloor(VIRTUAL_TOKEN_LIQUIDITY.sub(new BN("50000000000")));
+    const curve = await launchCoin();
+
+    await buy(curve, sol(1));
+    await expectError(buy(curve, sol(1)), "CalculationError");
+
+    await setFloor(new BN(0));
+  });
+
+  it("reports the curve withdrawable only after graduation", async () => {
+    const curve = await launchCoin();
+    const withdrawable = (): Promise<boolean> =>
+      program.methods
+        .isWithdrawable()
+        .accountsPartial(withdrawQueryAccounts(curve))
+        .view();
+
+    await buy(curve, sol(1));
+    assert.isFalse(await withdrawable());
+
+    // large enough to hit the sell limit and deactivate the curve
+    await buy(curve, sol(100));
+    assert.isTrue(await withdrawable());
+  });
+
+  it("raises the reserve ratio after a large buy", async () => {
+    const curve = await launchCoin();
+    const reserveRatio = (): Promise<BN> =>
+      program.methods
+        .getReserveRatio()
+        .accountsPartial(queryAccounts(curve))
+        .view();
+
+    const before = await reserveRatio();
+    // 30 SOL / 1.073e12 base units, scaled by 1e9
+    assert.equal(before.toNumber(), 27_958_993);
+
+    await buy(curve, sol(10));
+    assert.ok((await reserveRatio()).gt(before));
+  });
+
+  it("withholds a sell penalty that decays to zero after the window", async () => {
+    const window = 3;
+    const setSellPenalty = (bps: number, seconds: number) =>
+      program.methods
+        .setSellPenalty(bps, new BN(seconds))
+        .accountsPartial({ owner: wallet, globalState })
+        .rpc();
+    await setSellPenalty(5_000, window);
+
+    const curve = await launchCoin();
+    const accounts = {
+      ...tradeAccounts(curve, wallet),
+      traderPosition: traderPositionPda(curve, wallet),
+    };
+
+    // the penalty is the SOL taken out of the reserves but left in the escrow
+    const sellAndMeasurePenalty = async (tokenAmount: BN) => {
+      const reservesBefore = (
+        await program.account.bondingCurve.fetch(curve.bondingCurve)
+      ).virtualSolLiquidity;
+      const escrowBefore = await provider.connection.getBalance(curve.solEscrow);
+      await program.methods
+        .sellToken(tokenAmount, NO_MIN_SOL_OUT, null)
+        .accountsPartial(accounts)
+        .rpc();
+      const reservesAfter = (
+        await program.account.bondingCurve.fetch(curve.bondingCurve)
+      ).virtualSolLiquidity;
+      const escrowAfter = await provider.connection.getBalance(curve.solEscrow);
+      return reservesBefore
+        .sub(reservesAfter)
+        .subn(escrowBefore - escrowAfter);
+    };
+
+    await createBuyerAta(curve).rpc();
+    await program.methods.buyToken(sol(1), NO_MAX_PRICE, null, null).accountsPartial(accounts).rpc();
+    const half = (await tokenBalance(curve)).divn(2);
+
+    const early = await sellAndMeasurePenalty(half);
+    assert.ok(early.gtn(0));
+
+    await new Promise((resolve) => setTimeout(resolve, (window + 1) * 1000));
+    const late = await sellAndMeasurePenalty(half);
+    assert.ok(late.isZero());
+
+    await setSellPenalty(0, 0);
+  });
+
+  it("returns the sub-lamport starting price in Q64.64", async () => {
+    const curve = await launchCoin();
+
+    const price: BN = await program.methods
+      .getPriceQ64()
+      .accountsPartial(queryAccounts(curve))
+      .view();
+
+    const expected = VIRTUAL_SOL_LIQUIDITY.shln(64).div(VIRTUAL_TOKEN_LIQUIDITY);
+    assert.ok(price.eq(expected));
+    // below one lamport per base unit, so the integer part is zero
+    assert.ok(price.shrn(64).isZero());
+  });
+
+  it("emits CurveDefaultsUpdated with the before and after reserves", async () => {
+    const setCurveDefaults = (vsol: BN, vtoken: BN) =>
+      program.methods
+        .setCurveDefaults(vsol, vtoken)
+        .accountsPartial({ owner: wallet, globalState })
+        .rpc();
+
+    const newVsol = sol(40);
+    const newVtoken = VIRTUAL_TOKEN_LIQUIDITY.muln(2);
+    const signature = await setCurveDefaults(newVsol, newVtoken);
+
+    const event = (await eventsOf(signature)).find(
+      (e) => e.name === "curveDefaultsUpdated"
+    );
+    assert.ok(event);
+    assert.ok(event.data.oldVsol.eq(VIRTUAL_SOL_LIQUIDITY));
+    assert.ok(event.data.oldVtoken.eq(VIRTUAL_TOKEN_LIQUIDITY));
+    assert.ok(event.data.newVsol.eq(newVsol));
+    assert.ok(event.data.newVtoken.eq(newVtoken));
+
+    await setCurveDefaults(VIRTUAL_SOL_LIQUIDITY, VIRTUAL_TOKEN_LIQUIDITY);
+  });
+
+  it("rejects buys taking more than the maximum share of unsold tokens", async () => {
+    const setMaxBuyFraction = (bps: number) =>
+      program.methods
+        .setMaxBuyFractionBps(bps)
+        .accountsPartial({ owner: wallet, globalState })
+        .rpc();
+
+    // 10% of the unsold tokens
+    await setMaxBuyFraction(1_000);
+    const curve = await launchCoin();
+
+    await buy(curve, sol(1));
+    await expectError(buy(curve, sol(5)), "BuyTooLarge");
+
+    await setMaxBuyFraction(0);
+  });
+
+  it("still graduates a curve under the maximum buy share", async () => {
+    const setMaxBuyFraction = (bps: number) =>
+      program.methods
+        .setMaxBuyFractionBps(bps)
+        .accountsPartial({ owner: wallet, globalState })
+        .rpc();
+    await setMaxBuyFraction(5_000);
+    const curve = await launchCoin();
+    await createBuyerAta(curve).rpc();
+
+    // the whole sale is more than half of what is unsold
+    await expectError(buy(curve, sol(100)), "BuyTooLarge");
+
+    // take 49% of the sale, pricing the buy with the inverse curve
+    const state = await program.account.bondingCurve.fetch(curve.bondingCurve);
+    const toBuy = state.tokensToSell.muln(4_900).divn(10_000);
+    const k = state.virtualSolLiquidity.mul(state.virtualTokenLiquidity);
+    const tokenAfter = state.virtualTokenLiquidity.sub(toBuy);
+    await buy(
+      curve,
+      k.add(tokenAfter).subn(1).div(tokenAfter).sub(state.virtualSolLiquidity)
+    );
+
+    // the remaining 51% is over half of the unsold tokens but fits half of the sale
+    const partial = await program.account.bondingCurve.fetch(curve.bondingCurve);
+    const unsold = partial.tokensToSell.sub(partial.tokensSold);
+    assert.ok(unsold.gt(partial.tokensToSell.divn(2)));
+    await expectError(buy(curve, sol(100)), "BuyTooLarge");
+
+    // another 2% of the sale brings the tail within the cap, and the completing buy is allowed
+    const step = partial.tokensToSell.muln(200).divn(10_000);
+    const k2 = partial.virtualSolLiquidity.mul(partial.virtualTokenLiquidity);
+    const tokenAfter2 = partial.virtualTokenLiquidity.sub(step);
+    await buy(
+      curve,
+      k2.add(tokenAfter2).subn(1).div(tokenAfter2).sub(partial.virtualSolLiquidity)
+    );
+    await buy(curve, sol(100));
+
+    const graduated = await program.account.bondingCurve.fetch(curve.bondingCurve);
+    assert.isFalse(graduated.isActive);
+    assert.ok(graduated.tokensSold.eq(graduated.tokensToSell));
+
+    await setMaxBuyFraction(0);
+  });
+
+  it("compounds the buy's growth share on what the trade fee leaves", async () => {
+    const setFees = async (feeBps: number, growthBps: number) => {
+      await program.methods
+        .setFee(feeBps)
+        .accountsPartial({ owner: wallet, globalState })
+        .rpc();
+      await program.methods
+        .setLiquidityGrowthBps(growthBps)
+        .accountsPartial({ owner: wallet, globalState })
+        .rpc();
+    };
+    const curve = await launchCoin();
+    await setFees(100, 100);
+
+    const quoted = await program.methods
+      .getEffectiveFee(sol(1), true)
+      .accountsPartial({ ...queryAccounts(curve), trader: wallet, traderPosition: null })
+      .view();
+    // 1% trade fee, then 1% growth on the remaining 99%
+    assert.equal(quoted.feeBps, 199);
+    assert.ok(quoted.fee.eq(new BN(10_000_000 + 9_900_000)));
+
+    const vaultBefore = await provider.connection.getBalance(feeVault);
+    await buy(curve, sol(1));
+    assert.equal(
+      (await provider.connection.getBalance(feeVault)) - vaultBefore,
+      10_000_000
+    );
+
+    await setFees(0, 0);
+  });
+
+  it("quotes the sell penalty the real sell then charges", async () => {
+    const setSellPenalty = (bps: number, seconds: number) =>
+      program.methods
+        .setSellPenalty(bps, new BN(seconds))
+        .accountsPartial({ owner: wallet, globalState })
+        .rpc();
+    await setSellPenalty(5_000, 1_000);
+
+    const curve = await launchCoin();
+    const traderPosition = traderPositionPda(curve, wallet);
+    const accounts = { ...tradeAccounts(curve, wallet), traderPosition };
+    const effectiveFee = (amount: BN, isBuy: boolean) =>
+      program.methods
+        .getEffectiveFee(amount, isBuy)
+        .accountsPartial({
+          ...queryAccounts(curve),
+          trader: wallet,
+          traderPosition,
+        })
+        .view();
+
+    await createBuyerAta(curve).rpc();
+    await program.methods.buyToken(sol(1), NO_MAX_PRICE, null, null).accountsPartial(accounts).rpc();
+    const tokenAmount = await tokenBalance(curve);
+
+    const buyFee = await effectiveFee(sol(1), true);
+    assert.ok(buyFee.fee.isZero());
+
+    const quoted = await effectiveFee(tokenAmount, false);
+    assert.isAbove(quoted.feeBps, 0);
+    assert.isAtMost(quoted.feeBps, 5_000);
+
+    const reservesBefore = (
+      await program.account.bondingCurve.fetch(curve.bondingCurve)
+    ).virtualSolLiquidity;
+    const escrowBefore = await provider.connection.getBalance(curve.solEscrow);
+    await program.methods
+      .sellToken(tokenAmount, NO_MIN_SOL_OUT, null)
+      .accountsPartial(accounts)
+      .rpc();
+    const reservesAfter = (
+      await program.account.bondingCurve.fetch(curve.bondingCurve)
+    ).virtualSolLiquidity;
+    const escrowAfter = await provider.connection.getBalance(curve.solEscrow);
+    const charged = reservesBefore
+      .sub(reservesAfter)
+      .subn(escrowBefore - escrowAfter);
+
+    // a second or two of decay may pass between the quote and the sell
+    assert.ok(charged.lte(quoted.fee));
+    assert.ok(charged.gte(quoted.fee.muln(99).divn(100)));
+
+    await setSellPenalty(0, 0);
+  });
+
+  it("keeps k drift within tolerance over many trades", async () => {
+    const curve = await launchCoin();
+    const getK = (): Promise<BN> =>
+      program.methods.getK().accountsPartial(queryAccounts(curve)).view();
+    const { kToleranceBps } = await program.account.globalState.fetch(
+      globalState
+    );
+
+    const kStart = await getK();
+    for (const amount of [0.1, 1.3, 0.01, 2.5, 0.7]) {
+      await buy(curve, sol(amount));
+      const half = (await tokenBalance(curve)).divn(2);
+      await program.methods
+        .sellToken(half, NO_MIN_SOL_OUT, null)
+        .accountsPartial(tradeAccounts(curve, wallet))
+        .rpc();
+    }
+    const kEnd = await getK();
+
+    // every trade passed the on-chain check, and the total drift is upwards and tiny
+    assert.ok(kEnd.gte(kStart));
+    assert.ok(kEnd.sub(kStart).lte(kStart.muln(kToleranceBps).divn(10_000)));
+  });
+
+  it("requires the buyer token account to be created before buying", async () => {
+    const curve = await launchCoin();
+    const buyTokens = () =>
+      program.methods
+        .buyToken(sol(0.1), NO_MAX_PRICE, null, null)
+        .accountsPartial(tradeAccounts(curve, wallet))
+        .rpc();
+
+    await expectError(buyTokens(), "AccountNotInitialized");
+
+    await createBuyerAta(curve).rpc();
+    await buyTokens();
+    assert.ok((await tokenBalance(curve)).gtn(0));
+  });
+
+  it("returns launch info matching the curve account", async () => {
+    const { launchCount } = await program.account.globalState.fetch(
+      globalState
+    );
+    const curve = await launchCoin();
+
+    const info = await program.methods
+      .getLaunchInfo()
+      .accountsPartial(queryAccounts(curve))
+      .view();
+    const account = await program.account.bondingCurve.fetch(
+      curve.bondingCurve
+    );
+
+    assert.ok(info.tokenMint.equals(curve.mint));
+    assert.ok(info.creator.equals(wallet));
+    assert.equal(info.decimals, 6);
+    assert.equal(info.curveType, 0);
+    assert.ok(info.totalSupply.eq(account.totalSupply));
+    assert.ok(info.tokensToSell.eq(account.tokensToSell));
+    assert.ok(info.migrationReserve.eq(account.migrationReserve));
+    assert.ok(info.createdAt.eq(account.createdAt));
+    assert.ok(info.launchId.eq(launchCount));
+  });
+
+  it("deactivates a curve only once the escrow holds the graduation minimum", async () => {
+    const setMinGraduationSol = (amount: BN) =>
+      program.methods
+        .setMinGraduationSol(amount)
+        .accountsPartial({ owner: wallet, globalState })
+        .rpc();
+    await setMinGraduationSol(sol(2));
+
+    const curve = await launchCoin();
+    const deactivate = () =>
+      program.methods
+        .deactivateCurve()
+        .accountsPartial({ owner: wallet, ...queryAccounts(curve) })
+        .rpc();
+
+    await buy(curve, sol(1));
+    await expectError(deactivate(), "BelowGraduationMinimum");
+
+    await buy(curve, sol(1.5));
+    await deactivate();
+    const { isActive } = await program.account.bondingCurve.fetch(
+      curve.bondingCurve
+    );
+    assert.isFalse(isActive);
+
+    await setMinGraduationSol(new BN(0));
+  });
+
+  it("averages the entry price across a wallet's buys", async () => {
+    const setTracking = (enabled: boolean) =>
+      program.methods
+        .setTrackEntryPrices(enabled)
+        .accountsPartial({ owner: wallet, globalState })
+        .rpc();
+    await setTracking(true);
+
+    const curve = await launchCoin();
+    const traderPosition = traderPositionPda(curve, wallet);
+    const accounts = { ...tradeAccounts(curve, wallet), traderPosition };
+    await createBuyerAta(curve).rpc();
+
+    // Q64.64 price paid by a buy, matching the on-chain representation
+    const buyAndPrice = async (solAmount: BN) => {
+      const before = await tokenBalance(curve);
+      await program.methods.buyToken(solAmount, NO_MAX_PRICE, null, null).accountsPartial(accounts).rpc();
+      const tokens = (await tokenBalance(curve)).sub(before);
+      return solAmount.shln(64).div(tokens);
+    };
+
+    const first = await buyAndPrice(sol(1));
+    const second = await buyAndPrice(sol(1));
+    assert.ok(second.gt(first));
+
+    const average: BN = await program.methods
+      .getAvgEntryPrice()
+      .accountsPartial({ ...queryAccounts(curve), trader: wallet })
+      .view();
+    assert.ok(average.gt(first));
+    assert.ok(average.lt(second));
+
+    await setTracking(false);
+  });
+
+  it("rejects trades on a graduated curve with CurveGraduated", async () => {
+    const curve = await launchCoin();
+
+    // large enough to hit the sell limit and deactivate the curve
+    await buy(curve, sol(100));
+
+    await expectError(buy(curve, sol(0.1)), "CurveGraduated");
+    await expectError(
+      program.methods
+        .sellToken(new BN(1), NO_MIN_SOL_OUT, null)
+        .accountsPartial(tradeAccounts(curve, wallet))
+        .rpc(),
+      "CurveGraduated"
+    );
+  });
+
+  it("rounds the displayed price to the tick size", async () => {
+    const curve = await launchCoin();
+    const priceRounded = async (tickSize: number) =>
+      (
+        (await program.methods
+          .getPriceRounded(new BN(tickSize))
+          .accountsPartial(queryAccounts(curve))
+          .view()) as BN
+      ).toNumber();
+
+    // 30 SOL / 1.073e12 base units is 27_958.99 lamports per whole token
+    assert.equal(await priceRounded(0), 27_958);
+    assert.equal(await priceRounded(25), 27_950);
+    assert.equal(await priceRounded(100), 28_000);
+  });
+
+  it("recovers SOL sent to the global state by mistake", async () => {
+    const stray = sol(0.5).toNumber();
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        SystemProgram.transfer({
+          fromPubkey: wallet,
+          toPubkey: globalState,
+          lamports: stray,
+        })
+      )
+    );
+    const before = await provider.connection.getBalance(globalState);
+
+    await program.methods
+      .recoverStraySol()
+      .accountsPartial({ owner: wallet, globalState })
+      .rpc();
+
+    const after = await provider.connection.getBalance(globalState);
+    const { data } = await provider.connection.getAccountInfo(globalState);
+    const rentExempt =
+      await provider.connection.getMinimumBalanceForRentExemption(data.length);
+    assert.equal(after, rentExempt);
+    assert.isAtLeast(before - after, stray);
+  });
+
+  it("raises the floor price with every trade under liquidity growth", async () => {
+    const setGrowth = (bps: number) =>
+      program.methods
+        .setLiquidityGrowthBps(bps)
+        .accountsPartial({ owner: wallet, globalState })
+        .rpc();
+    await setGrowth(100);
+
+    const curve = await launchCoin();
+    const reserveRatio = (): Promise<BN> =>
+      program.methods
+        .getReserveRatio()
+        .accountsPartial(queryAccounts(curve))
+        .view();
+
+    // buying and selling the same tokens returns the token reserve to where it was,
+    // so the ratio there is the floor price
+    let floor = await reserveRatio();
+    for (const amount of [0.5, 1, 2]) {
+      await buy(curve, sol(amount));
+      await program.methods
+        .sellToken(await tokenBalance(curve), NO_MIN_SOL_OUT, null)
+        .accountsPartial(tradeAccounts(curve, wallet))
+        .rpc();
+
+      const next = await reserveRatio();
+      assert.ok(next.gt(floor));
+      floor = next;
+    }
+
+    await setGrowth(0);
+  });
+
+  it("reports both migration legs matching the on-chain balances", async () => {
+    const curve = await launchCoin();
+    await buy(curve, sol(1));
+    await buy(curve, sol(2));
+
+    const assets = await program.methods
+      .getMigrationAssets()
+      .accountsPartial(withdrawQueryAccounts(curve))
+      .view();
+
+    const escrow = await provider.connection.getBalance(curve.solEscrow);
+    const rentExempt =
+      await provider.connection.getMinimumBalanceForRentExemption(0);
+    const tokens = await provider.connection.getTokenAccountBalance(
+      curve.curveTokenAccount
+    );
+    assert.equal(assets.solAvailable.toNumber(), escrow - rentExempt);
+    assert.equal(assets.tokensAvailable.toString(), tokens.value.amount);
+  });
+
+  it("rejects a buy pushing the price above max_price", async () => {
+    const curve = await launchCoin();
+
+    const price = await program.methods
+      .getPriceRounded(new BN(0))
+      .accountsPartial(queryAccounts(curve))
+      .view();
+
+    // a 1 SOL buy moves the price well past the current one
+    await expectError(buy(curve, sol(1), price), "PriceTooHigh");
+
+    await buy(curve, sol(1), price.muln(2));
+  });
+
+  it("counts trades and SOL volume across a buy and a sell", async () => {
+    const curve = await launchCoin();
+    const stats = () =>
+      program.methods
+        .getTradeStats()
+        .accountsPartial(queryAccounts(curve))
+        .view();
+
+    await buy(curve, sol(1));
+    const afterBuy = await stats();
+    assert.equal(afterBuy.tradeCount.toNumber(), 1);
+    assert.equal(afterBuy.totalVolumeSol.toString(), sol(1).toString());
+    assert.equal(afterBuy.totalSolRaised.toString(), sol(1).toString());
+
+    const escrowBefore = await provider.connection.getBalance(curve.solEscrow);
+    await program.methods
+      .sellToken((await tokenBalance(curve)).divn(2), NO_MIN_SOL_OUT, null)
+      .accountsPartial(tradeAccounts(curve, wallet))
+      .rpc();
+    const paidOut =
+      escrowBefore - (await provider.connection.getBalance(curve.solEscrow));
+
+    const afterSell = await stats();
+    assert.equal(afterSell.tradeCount.toNumber(), 2);
+    assert.isTrue(afterSell.totalVolumeSol.gt(afterBuy.totalVolumeSol));
+    assert.equal(
+      afterSell.totalSolRaised.toNumber(),
+      sol(1).toNumber() - paidOut
+    );
+  });
+
+  it("counts SOL volume before the trade fee on both sides", async () => {
+    const setFee = (bps: number) =>
+      program.methods
+        .setFee(bps)
+        .accountsPartial({ owner: wallet, globalState })
+        .rpc();
+    const curve = await launchCoin();
+    const stats = () =>
+      program.methods
+        .getTradeStats()
+        .accountsPartial(queryAccounts(curve))
+        .view();
+    const escrowBalance = () => provider.connection.getBalance(curve.solEscrow);
+    await setFee(100);
+
+    // the buyer's whole payment counts, though the fee never reaches the escrow
+    let escrowBefore = await escrowBalance();
+    await buy(curve, sol(1));
+    const afterBuy = await stats();
+    const raised = (await escrowBalance()) - escrowBefore;
+    assert.equal(afterBuy.totalVolumeSol.toString(), sol(1).toString());
+    assert.isBelow(raised, sol(1).toNumber());
+    assert.equal(afterBuy.totalSolRaised.toNumber(), raised);
+
+    // the sale's SOL counts before the fee leaves the escrow with the payout
+    escrowBefore = await escrowBalance();
+    await program.methods
+      .sellToken((await tokenBalance(curve)).divn(2), NO_MIN_SOL_OUT, null)
+      .accountsPartial(tradeAccounts(curve, wallet))
+      .rpc();
+    const grossSold = escrowBefore - (await escrowBalance());
+    const afterSell = await stats();
+    assert.equal(
+      afterSell.totalVolumeSol.toNumber(),
+      sol(1).toNumber() + grossSold
+    );
+
+    await setFee(0);
+  });
+
+  it("rejects a config change inside the cooldown except a pause", async () => {
+    const cooldown = 2;
+    const setCooldown = (seconds: number) =>
+      program.methods
+        .setConfigChangeCooldown(new BN(seconds))
+        .accountsPartial({ owner: wallet, globalState })
+        .rpc();
+
+    await expectError(setCooldown(-1), "InvalidCooldown");
+    await expectError(setCooldown(7 * 24 * 60 * 60 + 1), "InvalidCooldown");
+
+    await setCooldown(cooldown);
+    await expectError(
+      program.methods
+        .setMinTokensPerBuy(new BN(0))
+        .accountsPartial({ owner: wallet, globalState })
+        .rpc(),
+      "ConfigChangeTooSoon"
+    );
+
+    // pausing skips the cooldown so trading can always be halted at once
+    for (const isPaused of [true, false]) {
+      await program.methods
+        .setPaused(isPaused)
+        .accountsPartial({ owner: wallet, globalState })
+        .rpc();
+    }
+
+    await new Promise((resolve) => setTimeout(resolve, (cooldown + 1) * 1000));
+    await setCooldown(0);
+  });
+
+  it("quotes the smallest buy that receives one whole token", async () => {
+    const curve = await launchCoin();
+    const minSol = await program.methods
+      .minSolForOneToken()
+      .accountsPartial(queryAccounts(curve))
+      .view();
+
+    await buy(curve, minSol);
+
+    const mint = await provider.connection.getTokenSupply(curve.mint);
+    const oneToken = new BN(10).pow(new BN(mint.value.decimals));
+    assert.isTrue((await tokenBalance(curve)).gte(oneToken));
+  });
+
+  it("launches and makes the creator's first buy in one instruction", async () => {
+    const launchAndBuy = (mint: anchor.web3.Keypair, minTokensOut: BN) => {
+      const curve = curvePdas(mint.publicKey);
+      return program.methods
+        .launchAndBuy(
+          "Mini Pump Token",
+          "MPT",
+          "https://example.com/mpt.json",
+          sol(1),
+          minTokensOut
+        )
+        .accountsPartial({
+          launch: {
+            payer: wallet,
+            globalState,
+            bondingCurve: curve.bondingCurve,
+            bondingCurveSolEscrow: curve.solEscrow,
+            tokenMint: mint.publicKey,
+            metadata: curve.metadata,
+            bondingCurveTokenAccount: curve.curveTokenAccount,
+            symbolRegistry: null,
+            treasury,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            tokenMetadataProgram: METADATA_PROGRAM_ID,
+          },
+          creatorTokenAccount: anchor.utils.token.associatedAddress({
+            mint: mint.publicKey,
+            owner: wallet,
+          }),
+          traderPosition: null,
+          feeVault,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .preInstructions([
+          anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({
+            units: 400_000,
+          }),
+        ])
+        .signers([mint])
+        .rpc();
+    };
+
+    // the creator's buy is the first trade, priced from the default reserves
+    const k = VIRTUAL_SOL_LIQUIDITY.mul(VIRTUAL_TOKEN_LIQUIDITY);
+    const denominator = VIRTUAL_SOL_LIQUIDITY.add(sol(1));
+    const tokenAfter = k.add(denominator).subn(1).div(denominator);
+    const expected = VIRTUAL_TOKEN_LIQUIDITY.sub(tokenAfter);
+
+    await expectError(
+      launchAndBuy(Keypair.generate(), expected.addn(1)),
+      "SlippageExceeded"
+    );
+
+    const mint = Keypair.generate();
+    await launchAndBuy(mint, expected);
+    const curve = curvePdas(mint.publicKey);
+
+    assert.equal((await tokenBalance(curve)).toString(), expected.toString());
+    const state = await program.account.bondingCurve.fetch(curve.bondingCurve);
+    assert.isTrue(state.isActive);
+    assert.equal(state.tradeCount.toNumber(), 1);
+    assert.equal(state.tokensSold.toString(), expected.toString());
+  });
+
+  it("reports the curve's age and time since its last trade", async () => {
+    const curve = await launchCoin();
+    await buy(curve, sol(0.1));
+
+    const timing = await program.methods
+      .getTiming()
+      .accountsPartial(queryAccounts(curve))
+      .view();
+    const state = await program.account.bondingCurve.fetch(curve.bondingCurve);
+    const slot = await provider.connection.getSlot();
+    const now = await provider.connection.getBlockTime(slot);
+
+    assert.isAtLeast(timing.ageSeconds.toNumber(), 0);
+    assert.isAtMost(
+      timing.secondsSinceLastTrade.toNumber(),
+      timing.ageSeconds.toNumber()
+    );
+    assert.approximately(
+      timing.ageSeconds.toNumber(),
+      now - state.createdAt.toNumber(),
+      2
+    );
+    assert.approximately(
+      timing.secondsSinceLastTrade.toNumber(),
+      now - state.lastTradeTimestamp.toNumber(),
+      2
+    );
+  });
+
+  it("rejects a buy expecting a different config epoch", async () => {
+    const curve = await launchCoin();
+    const { configEpoch } = await program.account.bondingCurve.fetch(
+      curve.bondingCurve
+    );
+
+    // a curve launched after the defaults change records the next epoch
+    await program.methods
+      .setCurveDefaults(VIRTUAL_SOL_LIQUIDITY, VIRTUAL_TOKEN_LIQUIDITY)
+      .accountsPartial({ owner: wallet, globalState })
+      .rpc();
+    const newer = await launchCoin();
+    await expectError(
+      buy(newer, sol(0.1), NO_MAX_PRICE, configEpoch),
+      "CurveConfigChanged"
+    );
+
+    await buy(curve, sol(0.1), NO_MAX_PRICE, configEpoch);
+    await buy(newer, sol(0.1), NO_MAX_PRICE, configEpoch.addn(1));
+  });
+
+  it("quotes the price impact a trade then actually has", async () => {
+    const curve = await launchCoin();
+    const price = () =>
+      program.methods
+        .getPriceQ64()
+        .accountsPartial(queryAccounts(curve))
+        .view();
+    const quote = (amount: BN, isBuy: boolean) =>
+      program.methods
+        .quotePriceImpact(amount, isBuy)
+        .accountsPartial(queryAccounts(curve))
+        .view();
+    const impactOf = (before: BN, after: BN) =>
+      after.sub(before).abs().muln(10_000).div(before).toNumber();
+
+    assert.equal((await quote(new BN(1), true)).toNumber(), 0);
+
+    const buyQuote = await quote(sol(2), true);
+    const beforeBuy = await price();
+    await buy(curve, sol(2));
+    const afterBuy = await price();
+    assert.isAbove(buyQuote.toNumber(), 0);
+    assert.approximately(buyQuote.toNumber(), impactOf(beforeBuy, afterBuy), 1);
+
+    const tokens = await tokenBalance(curve);
+    const sellQuote = await quote(tokens, false);
+    await program.methods
+      .sellToken(tokens, NO_MIN_SOL_OUT, null)
+      .accountsPartial(tradeAccounts(curve, wallet))
+      .rpc();
+    assert.approximately(
+      sellQuote.toNumber(),
+      impactOf(afterBuy, await price()),
+      1
+    );
+
+    // a buy that buy_token would reject has no impact to quote
+    const setMaxBuyFraction = (bps: number) =>
+      program.methods
+        .setMaxBuyFractionBps(bps)
+        .accountsPartial({ owner: wallet, globalState })
+        .rpc();
+    await setMaxBuyFraction(100);
+    await expectError(quote(sol(10), true), "BuyTooLarge");
+    await setMaxBuyFraction(0);
+  });
+
+  it("emits TradeEvent with the post-trade reserves", async () => {
+    const curve = await launchCoin();
+    const checkTradeEvent = async (signature: string, isBuy: boolean) => {
+      const event = (await eventsOf(signature)).find(
+        (e) => e.name === "tradeEvent"
+      );
+      assert.ok(event, "expected a tradeEvent");
+      const state = await program.account.bondingCurve.fetch(
+        curve.bondingCurve
+      );
+      assert.equal(event.data.isBuy, isBuy);
+      assert.equal(
+        event.data.virtualSolLiquidity.toString(),
+        state.virtualSolLiquidity.toString()
+      );
+      assert.equal(
+        event.data.virtualTokenLiquidity.toString(),
+        state.virtualTokenLiquidity.toString()
+      );
+      assert.equal(
+        event.data.tokensSold.toString(),
+        state.tokensSold.toString()
+      );
+    };
+
+    await checkTradeEvent(await buy(curve, sol(1)), true);
+    const signature = await program.methods
+      .sellToken((await tokenBalance(curve)).divn(2), NO_MIN_SOL_OUT, null)
+      .accountsPartial(tradeAccounts(curve, wallet))
+      .rpc();
+    await checkTradeEvent(signature, false);
+  });
+
+  it("records the upgrade authority target and emits an event", async () => {
+    const target = Keypair.generate().publicKey;
+    const setTarget = (key: anchor.web3.PublicKey) =>
+      program.methods
+        .setUpgradeAuthorityTarget(key)
+        .accountsPartial({ owner: wallet, globalState })
+        .rpc();
+
+    const event = (await eventsOf(await setTarget(target))).find(
+      (e) => e.name === "upgradeAuthorityTargetSet"
+    );
+    assert.ok(event, "expected an upgradeAuthorityTargetSet event");
+    assert.isTrue(event.data.newTarget.equals(target));
+
+    const state = await program.account.globalState.fetch(globalState);
+    assert.isTrue(state.upgradeAuthorityTarget.equals(target));
+
+    await setTarget(PublicKey.default);
+  });
+
+  it("rejects trades whose output falls outside a bps slippage tolerance", async () => {
+    const curve = await launchCoin();
+    const buyWithin = async (bps: number) =>
+      program.methods
+        .buyToken(sol(2), NO_MAX_PRICE, null, { bps: [bps] })
+        .accountsPartial(tradeAccounts(curve, wallet))
+        .preInstructions([await createBuyerAta(curve).instruction()])
+        .rpc();
+    const sellWithin = async (bps: number) =>
+      program.methods
+        .sellToken((await tokenBalance(curve)).divn(2), NO_MIN_SOL_OUT, { bps: [bps] })
+        .accountsPartial(tradeAccounts(curve, wallet))
+        .rpc();
+
+    // a 2 SOL buy moves the price by several percent, far beyond 1 bps
+    await expectError(buyWithin(1), "SlippageExceeded");
+    await buyWithin(2_000);
+
+    await expectError(sellWithin(1), "SlippageExceeded");
+    await sellWithin(2_000);
+  });
+
+  it("reports the whole tokens left before graduation", async () => {
+    const curve = await launchCoin();
+    await buy(curve, sol(1));
+    await buy(curve, sol(0.5));
+
+    const view = (method: "getTokensRemaining" | "getTokensRemainingWhole") =>
+      program.methods[method]()
+        .accountsPartial(queryAccounts(curve))
+        .view();
+    const mint = await provider.connection.getTokenSupply(curve.mint);
+    const oneToken = new BN(10).pow(new BN(mint.value.decimals));
+
+    const remaining = await view("getTokensRemaining");
+    const remainingWhole = await view("getTokensRemainingWhole");
+    assert.equal(
+      remainingWhole.toString(),
+      remaining.div(oneToken).toString()
+    );
+  });
+
+  it("computes the tokens a wallet in loss must sell to break even", async () => {
+    const setTracking = (enabled: boolean) =>
+      program.methods
+        .setTrackEntryPrices(enabled)
+        .accountsPartial({ owner: wallet, globalState })
+        .rpc();
+    const curve = await launchCoin();
+    const traderPosition = traderPositionPda(curve, wallet);
+
+    // untracked tokens bought early, part of which is later dumped to push the price down
+    await buy(curve, sol(3));
+    const early = await tokenBalance(curve);
+
+    await setTracking(true);
+    await program.methods
+      .buyToken(sol(1), NO_MAX_PRICE, null, null)
+      .accountsPartial({ ...tradeAccounts(curve, wallet), traderPosition })
+      .rpc();
+    await setTracking(false);
+
+    await program.methods
+      .sellToken(early.divn(2), NO_MIN_SOL_OUT, null)
+      .accountsPartial(tradeAccounts(curve, wallet))
+      .rpc();
+
+    const breakEven = await program.methods
+      .getBreakEvenSellTokens()
+      .accountsPartial({ ...queryAccounts(curve), trader: wallet })
+      .view();
+    const position = await program.account.traderPosition.fetch(traderPosition);
+    assert.isFalse(breakEven.inProfit);
+    assert.isTrue(breakEven.tokensToSell.gt(position.tokensBought));
+
+    const escrowBefore = await provider.connection.getBalance(curve.solEscrow);
+    await program.methods
+      .sellToken(breakEven.tokensToSell, NO_MIN_SOL_OUT, null)
+      .accountsPartial(tradeAccounts(curve, wallet))
+      .rpc();
+    const paidOut =
+      escrowBefore - (await provider.connection.getBalance(curve.solEscrow));
+    assert.isAtLeast(paidOut, position.solSpent.toNumber());
+  });
+
+  it("quotes the SOL that buys out the curve and graduates it", async () => {
+    const curve = await launchCoin();
+    await buy(curve, sol(1));
+
+    const solToGraduate = await program.methods
+      .solToGraduate()
+      .accountsPartial(queryAccounts(curve))
+      .view();
+    await buy(curve, solToGraduate);
+
+    const state = await program.account.bondingCurve.fetch(curve.bondingCurve);
+    assert.isFalse(state.isActive);
+    const remaining = await program.methods
+      .solToGraduate()
+      .accountsPartial(queryAccounts(curve))
+      .view();
+    assert.equal(remaining.toNumber(), 0);
+  });
+
+  it("rejects a buyer holding less than the minimum beyond the purchase", async () => {
+    const setMinBuyerLamports = (lamports: BN) =>
+      program.methods
+        .setMinBuyerLamports(lamports)
+        .accountsPartial({ owner: wallet, globalState })
+        .rpc();
+    const curve = await launchCoin();
+
+    const buyer = Keypair.generate();
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        SystemProgram.transfer({
+          fromPubkey: wallet,
+          toPubkey: buyer.publicKey,
+          lamports: LAMPORTS_PER_SOL,
+        })
+      )
+    );
+    await createBuyerAta(curve, buyer.publicKey).signers([buyer]).rpc();
+    const buyAs = (signer: anchor.web3.Keypair) =>
+      program.methods
+        .buyToken(sol(0.1), NO_MAX_PRICE, null, null)
+        .accountsPartial(tradeAccounts(curve, signer.publicKey))
+        .signers([signer])
+        .rpc();
+
+    await setMinBuyerLamports(sol(2));
+    await expectError(buyAs(buyer), "BuyerIneligible");
+    await buy(curve, sol(0.1));
+
+    await setMinBuyerLamports(new BN(0));
+    await buyAs(buyer);
+  });
+
+  it("emits only the compact trade event under minimal verbosity", async () => {
+    const setVerbosity = (level: number) =>
+      program.methods
+        .setEventVerbosity(level)
+        .accountsPartial({ owner: wallet, globalState })
+        .rpc();
+    const curve = await launchCoin();
+
+    await setVerbosity(1);
+    const events = await eventsOf(await buy(curve, sol(0.5)));
+    await setVerbosity(0);
+
+    const minimal = events.find((e) => e.name === "tradeEventMinimal");
+    assert.ok(minimal, "expected a tradeEventMinimal");
+    assert.isUndefined(events.find((e) => e.name === "tradeEvent"));
+    assert.equal(minimal.data.solAmount.toString(), sol(0.5).toString());
+    assert.equal(minimal.data.seq.toNumber(), 1);
+    assert.hasAllKeys(minimal.data, ["solAmount", "tokenAmount", "seq"]);
+
+    await expectError(setVerbosity(2), "InvalidEventVerbosity");
+  });
+
+  it("keeps the peak of tokens sold after a sell", async () => {
+    const curve = await launchCoin();
+    const peak = () =>
+      program.methods
+        .getPeakSupply()
+        .accountsPartial(queryAccounts(curve))
+        .view();
+
+    await buy(curve, sol(1));
+    const { tokensSold } = await program.account.bondingCurve.fetch(
+      curve.bondingCurve
+    );
+    assert.equal((await peak()).toString(), tokensSold.toString());
+
+    await program.methods
+      .sellToken((await tokenBalance(curve)).divn(2), NO_MIN_SOL_OUT, null)
+      .accountsPartial(tradeAccounts(curve, wallet))
+      .rpc();
+    const after = await program.account.bondingCurve.fetch(curve.bondingCurve);
+    assert.isTrue(after.tokensSold.lt(tokensSold));
+    assert.equal((await peak()).toString(), tokensSold.toString());
+  });
+
+  it("rejects launches while paused but keeps trading open", async () => {
+    const setLaunchesPaused = (paused: boolean) =>
+      program.methods
+        .setLaunchesPaused(paused)
+        .accountsPartial({ owner: wallet, globalState })
+        .rpc();
+    const curve = await launchCoin();
+
+    await setLaunchesPaused(true);
+    await expectError(launchCoin(), "LaunchesPaused");
+    await buy(curve, sol(0.5));
+    await program.methods
+      .sellToken(await tokenBalance(curve), NO_MIN_SOL_OUT, null)
+      .accountsPartial(tradeAccounts(curve, wallet))
+      .rpc();
+
+    await setLaunchesPaused(false);
+    await launchCoin();
+  });
+
+  it("previews a buy near the sell limit with the capped output", async () => {
+    const curve = await launchCoin();
+    const solToGraduate = await program.methods
+      .solToGraduate()
+      .accountsPartial(queryAccounts(curve))
+      .view();
+    await buy(curve, solToGraduate.muln(9).divn(10));
+
+    const effective = await program.methods
+      .quoteBuyEffective(solToGraduate)
+      .accountsPartial(queryAccounts(curve))
+      .view();
+    const remaining = await program.methods
+      .getTokensRemaining()
+      .accountsPartial(queryAccounts(curve))
+      .view();
+
+    // the plain curve formula ignores the sell limit
+    const state = await program.account.bondingCurve.fetch(curve.bondingCurve);
+    const k = state.virtualSolLiquidity.mul(state.virtualTokenLiquidity);
+    const denominator = state.virtualSolLiquidity.add(solToGraduate);
+    const naive = state.virtualTokenLiquidity.sub(
+      k.add(denominator).subn(1).div(denominator)
+    );
+
+    assert.equal(effective.tokensOut.toString(), remaining.toString());
+    assert.isTrue(naive.gt(effective.tokensOut));
+    // a clamped buy is only charged what the remaining tokens cost
+    const stillNeeded: BN = await program.methods
+      .solToGraduate()
+      .accountsPartial(queryAccounts(curve))
+      .view();
+    assert.equal(effective.solCharged.toString(), stillNeeded.toString());
+    assert.equal(
+      effective.solRefunded.toString(),
+      solToGraduate.sub(stillNeeded).toString()
+    );
+    assert.equal(effective.fee.toNumber(), 0);
+  });
+
+  it("quotes the tokens for a share of the circulating supply", async () => {
+    const curve = await launchCoin();
+    await buy(curve, sol(2));
+
+    const quote = await program.methods
+      .tokensForOwnershipBps(100)
+      .accountsPartial(queryAccounts(curve))
+      .view();
+    const { tokensSold } = await program.account.bondingCurve.fetch(
+      curve.bondingCurve
+    );
+    assert.equal(
+      quote.tokenAmount.toString(),
+      tokensSold.muln(100).divn(10_000).toString()
+    );
+
+    // buying the quoted SOL receives at least the quoted tokens
+    const before = await tokenBalance(curve);
+    await buy(curve, quote.solRequired);
+    assert.isTrue((await tokenBalance(curve)).sub(before).gte(quote.tokenAmount));
+  });
+
+  it("refuses to migrate a curve deactivated before any trade", async () => {
+    const curve = await launchCoin();
+    await program.methods
+      .deactivateCurve()
+      .accountsPartial({ owner: wallet, ...queryAccounts(curve) })
+      .rpc();
+
+    // is_withdrawable shares withdraw_funds' preconditions, NothingToMigrate included
+    const withdrawable = await program.methods
+      .isWithdrawable()
+      .accountsPartial(withdrawQueryAccounts(curve))
+      .view();
+    assert.isFalse(withdrawable);
+
+    await createBuyerAta(curve).rpc();
+    await expectError(
+      program.methods.withdrawFunds().accountsPartial(withdrawAccounts(curve)).rpc(),
+      "NothingToMigrate"
+    );
+  });
+
+  it("values the full supply above the circulating market cap", async () => {
+    const curve = await launchCoin();
+    await buy(curve, sol(2));
+
+    const fdv = await program.methods
+      .getFdv()
+      .accountsPartial(queryAccounts(curve))
+      .view();
+    const state = await program.account.bondingCurve.fetch(curve.bondingCurve);
+    const supply = new BN(
+      (await provider.connection.getTokenSupply(curve.mint)).value.amount
+    );
+    const valueOf = (tokens: BN) =>
+      state.virtualSolLiquidity.mul(tokens).div(state.virtualTokenLiquidity);
+    const marketCap = valueOf(state.tokensSold);
+
+    assert.equal(fdv.toString(), valueOf(supply).toString());
+    // fdv / market cap equals supply / circulating, up to rounding
+    assert.isTrue(fdv.gt(marketCap));
+    assert.approximately(
+      fdv.mul(state.tokensSold).div(supply).toNumber(),
+      marketCap.toNumber(),
+      2
+    );
+  });
+
+  it("rejects trades routed to an escrow other than the curve's PDA", async () => {
+    const curve = await launchCoin();
+    const other = await launchCoin();
+    await createBuyerAta(curve).rpc();
+
+    // neither an arbitrary account nor another curve's escrow may stand in for it
+    for (const solEscrow of [Keypair.generate().publicKey, other.solEscrow]) {
+      await expectError(
+        program.methods
+          .buyToken(sol(0.1), NO_MAX_PRICE, null, null)
+          .accountsPartial({ ...tradeAccounts(curve, wallet), solEscrow })
+          .rpc(),
+        "ConstraintSeeds"
+      );
+    }
+    const escrow = await provider.connection.getBalance(curve.solEscrow);
+    assert.equal(
+      escrow,
+      await provider.connection.getMinimumBalanceForRentExemption(0)
+    );
+  });
+
+  it("summarizes several curves in one call", async () => {
+    const first = await launchCoin();
+    const second = await launchCoin();
+    await createBuyerAta(first).rpc();
+    await buy(first, sol(0.5));
+
+    const summaries = await program.methods
+      .getCurvesSummary()
+      .remainingAccounts(
+        [first, second].map((c) => ({
+          pubkey: c.bondingCurve,
+          isSigner: false,
+          isWritable: false,
+        }))
+      )
+      .view();
+
+    assert.equal(summaries.length, 2);
+    for (const [summary, curve] of [
+      [summaries[0], first],
+      [summaries[1], second],
+    ] as const) {
+      const state = await program.account.bondingCurve.fetch(curve.bondingCurve);
+      const price = await program.methods
+        .getPriceQ64()
+        .accountsPartial(queryAccounts(curve))
+        .view();
+      assert.ok(summary.tokenMint.equals(curve.mint));
+      assert.ok(summary.priceQ64.eq(price));
+      assert.ok(summary.tokensSold.eq(state.tokensSold));
+      assert.isTrue(summary.isActive);
+    }
+    assert.ok(summaries[0].tokensSold.gtn(0));
+    assert.ok(summaries[1].tokensSold.eqn(0));
+    assert.ok(summaries[0].priceQ64.gt(summaries[1].priceQ64));
+  });
+
+  it("launches, trades and graduates with a stored non-default supply split", async () => {
+    const setSplit = (total: BN, toSell: BN) =>
+      program.methods
+        .setLaunchSupplySplit(total, toSell)
+        .accountsPartial({ owner: wallet, globalState })
+        .rpc();
+    const defaults = await program.account.globalState.fetch(globalState);
+
+    // the sale must leave a migration reserve behind
+    await expectError(
+      setSplit(new BN(1_000_000), new BN(1_000_000)),
+      "SupplyReconciliationError"
+    );
+    // and stay below the virtual token reserve buys draw from
+    await expectError(
+      setSplit(defaults.virtualTokenLiquidity.muln(2), defaults.virtualTokenLiquidity),
+      "SupplyExceedsLiquidity"
+    );
+
+    const totalSupply = new BN("500000000000000");
+    const tokensToSell = new BN("400000000000");
+    const migrationReserve = totalSupply.sub(tokensToSell);
+
+    // the supply must fit the launch ceiling, which is in whole tokens
+    const setCeiling = (ceiling: BN) =>
+      program.methods
+        .setMaxTotalSupplyPerLaunch(ceiling)
+        .accountsPartial({ owner: wallet, globalState })
+        .rpc();
+    await setCeiling(totalSupply.divn(1_000_000).subn(1));
+    await expectError(setSplit(totalSupply, tokensToSell), "SupplyTooLarge");
+    await setCeiling(new BN(0));
+
+    await setSplit(totalSupply, tokensToSell);
+
+    const curve = await launchCoin();
+    const launched = await program.account.bondingCurve.fetch(curve.bondingCurve);
+    assert.ok(launched.totalSupply.eq(totalSupply));
+    assert.ok(launched.tokensToSell.eq(tokensToSell));
+    assert.ok(launched.migrationReserve.eq(migrationReserve));
+    const minted = await provider.connection.getTokenAccountBalance(
+      curve.curveTokenAccount
+    );
+    assert.equal(minted.value.amount, totalSupply.toString());
+
+    // buying out the curve stops at the stored sale amount and leaves the reserve behind
+    await createBuyerAta(curve).rpc();
+    await buy(curve, sol(100));
+    const graduated = await program.account.bondingCurve.fetch(curve.bondingCurve);
+    assert.isFalse(graduated.isActive);
+    assert.ok(graduated.tokensSold.eq(tokensToSell));
+
+    const assets = await program.methods
+      .getMigrationAssets()
+      .accountsPartial(withdrawQueryAccounts(curve))
+      .view();
+    assert.ok(assets.tokensAvailable.eq(migrationReserve));
+
+    await setSplit(defaults.totalTokensToMint, defaults.tokensToSell);
+  });
+
+  it("reports whether a buy would graduate the curve", async () => {
+    const curve = await launchCoin();
+    await createBuyerAta(curve).rpc();
+    await buy(curve, sol(20));
+
+    const wouldGraduate = (solAmount: BN) =>
+      program.methods
+        .wouldGraduate(solAmount)
+        .accountsPartial(queryAccounts(curve))
+        .view();
+    const toGraduate: BN = await program.methods
+      .solToGraduate()
+      .accountsPartial(queryAccounts(curve))
+      .view();
+
+    assert.isTrue(await wouldGraduate(toGraduate));
+    assert.isFalse(await wouldGraduate(sol(0.1)));
+
+    // the view matches what the buy then does
+    await buy(curve, toGraduate);
+    const { isActive } = await program.account.bondingCurve.fetch(
+      curve.bondingCurve
+    );
+    assert.isFalse(isActive);
+  });
+
+  it("rejects a sell below min_sol_out without moving tokens or SOL", async () => {
+    const curve = await launchCoin();
+    await createBuyerAta(curve).rpc();
+    await buy(curve, sol(1));
+
+    const tokensBefore = await tokenBalance(curve);
+    const escrowBefore = await provider.connection.getBalance(curve.solEscrow);
+
+    // more than the escrow could ever pay for these tokens
+    await expectError(
+      program.methods
+        .sellToken(tokensBefore, sol(2), null)
+        .accountsPartial(tradeAccounts(curve, wallet))
+        .rpc(),
+      "SlippageExceeded"
+    );
+
+    assert.ok((await tokenBalance(curve)).eq(tokensBefore));
+    assert.equal(
+      await provider.connection.getBalance(curve.solEscrow),
+      escrowBefore
+    );
+
+    // a reachable minimum lets the same sell through
+    await program.methods
+      .sellToken(tokensBefore, sol(0.9), null)
+      .accountsPartial(tradeAccounts(curve, wallet))
+      .rpc();
+    assert.ok((await tokenBalance(curve)).isZero());
+  });
+
+  it("caps the number of active curves until one graduates", async () => {
+    const setMaxActiveCurves = (max: BN) =>
+      program.methods
+        .setMaxActiveCurves(max)
+        .accountsPartial({ owner: wallet, globalState })
+        .rpc();
+    const activeCurves = async () =>
+      (await program.account.globalState.fetch(globalState)).activeCurveCount;
+
+    const before = await activeCurves();
+    await setMaxActiveCurves(before.addn(1));
+
+    const curve = await launchCoin();
+    assert.ok((await activeCurves()).eq(before.addn(1)));
+    await expectError(launchCoin(), "TooManyActiveCurves");
+
+    // graduating the new curve frees its slot
+    await createBuyerAta(curve).rpc();
+    await buy(curve, sol(100));
+    assert.ok((await activeCurves()).eq(before));
+    await launchCoin();
+
+    await setMaxActiveCurves(new BN(0));
+  });
+
+  it("quotes the SOL for selling a wallet's whole balance", async () => {
+    const curve = await launchCoin();
+    await createBuyerAta(curve).rpc();
+    await buy(curve, sol(1.5));
+
+    const quote: BN = await program.methods
+      .quoteSellAll()
+      .accountsPartial({
+        ...queryAccounts(curve),
+        trader: wallet,
+        traderTokenAccount: anchor.utils.token.associatedAddress({
+          mint: curve.mint,
+          owner: wallet,
+        }),
+        traderPosition: null,
+      })
+      .view();
+
+    const escrowBefore = await provider.connection.getBalance(curve.solEscrow);
+    await program.methods
+      .sellToken(await tokenBalance(curve), NO_MIN_SOL_OUT, null)
+      .accountsPartial(tradeAccounts(curve, wallet))
+      .rpc();
+    const escrowAfter = await provider.connection.getBalance(curve.solEscrow);
+
+    assert.ok((await tokenBalance(curve)).isZero());
+    assert.equal(escrowBefore - escrowAfter, quote.toNumber());
+  });
+
+  it("charges a buy at the sell limit only for the tokens left", async () => {
+    const curve = await launchCoin();
+    await createBuyerAta(curve).rpc();
+    const queryView = (method: "getTokensRemaining" | "solToGraduate") =>
+      program.methods[method]()
+        .accountsPartial(queryAccounts(curve))
+        .view() as Promise<BN>;
+
+    // buy all but about one whole token, pricing the buy with the inverse curve
+    const oneToken = new BN(1_000_000);
+    const state = await program.account.bondingCurve.fetch(curve.bondingCurve);
+    const toBuy = (await queryView("getTokensRemaining")).sub(oneToken);
+    const k = state.virtualSolLiquidity.mul(state.virtualTokenLiquidity);
+    const tokenAfter = state.virtualTokenLiquidity.sub(toBuy);
+    await buy(
+      curve,
+      k.add(tokenAfter).subn(1).div(tokenAfter).sub(state.virtualSolLiquidity)
+    );
+    const remaining = await queryView("getTokensRemaining");
+    assert.ok(remaining.gtn(0) && remaining.lte(oneToken));
+
+    const cost = await queryView("solToGraduate");
+    const before = await program.account.bondingCurve.fetch(curve.bondingCurve);
+    const escrowBefore = await provider.connection.getBalance(curve.solEscrow);
+    const walletBefore = await provider.connection.getBalance(wallet);
+    await buy(curve, sol(10));
+
+    const after = await program.account.bondingCurve.fetch(curve.bondingCurve);
+    assert.isFalse(after.isActive);
+    assert.ok(after.tokensSold.eq(after.tokensToSell));
+    // the reserves only move by the SOL actually consumed
+    assert.ok(after.virtualSolLiquidity.eq(before.virtualSolLiquidity.add(cost)));
+    assert.ok(after.tokensSold.sub(before.tokensSold).eq(remaining));
+
+    // the escrow takes the price of the last tokens and the rest stays with the buyer
+    const escrowAfter = await provider.connection.getBalance(curve.solEscrow);
+    assert.equal(escrowAfter - escrowBefore, cost.toNumber());
+    assert.isBelow(cost.toNumber(), sol(0.01).toNumber());
+    const spent = walletBefore - (await provider.connection.getBalance(wallet));
+    assert.isBelow(spent, sol(0.02).toNumber());
+  });
+
+  it("rejects launches with an empty name or symbol", async () => {
+    await expectError(launchCoin({ name: "" }), "EmptyMetadataField");
+    await expectError(launchCoin({ symbol: "" }), "EmptyMetadataField");
+  });
+
+  it("collects the trade fee into the fee vault", async () => {
+    const setFee = (bps: number) =>
+      program.methods
+        .setFee(bps)
+        .accountsPartial({ owner: wallet, globalState })
+        .rpc();
+    const curve = await launchCoin();
+    await createBuyerAta(curve).rpc();
+    const vaultBalance = () => provider.connection.getBalance(feeVault);
+
+    // without a fee the vault takes nothing
+    let before = await vaultBalance();
+    await buy(curve, sol(0.1));
+    assert.equal(await vaultBalance(), before);
+
+    await setFee(100);
+    const amount = sol(0.1);
+    before = await vaultBalance();
+    const reserveBefore = (
+      await program.account.bondingCurve.fetch(curve.bondingCurve)
+    ).virtualSolLiquidity;
+    await buy(curve, amount);
+    const buyFee = amount.muln(100).divn(10_000);
+    assert.equal((await vaultBalance()) - before, buyFee.toNumber());
+    // the fee never reaches the curve's reserve
+    const reserveAfter = (
+      await program.account.bondingCurve.fetch(curve.bondingCurve)
+    ).virtualSolLiquidity;
+    assert.ok(reserveAfter.sub(reserveBefore).eq(amount.sub(buyFee)));
+
+    const tokens = await tokenBalance(curve);
+    const state = await program.account.bondingCurve.fetch(curve.bondingCurve);
+    const k = state.virtualSolLiquidity.mul(state.virtualTokenLiquidity);
+    const solOut = state.virtualSolLiquidity.sub(
+      k.add(state.virtualTokenLiquidity.add(tokens).subn(1)).div(
+        state.virtualTokenLiquidity.add(tokens)
+      )
+    );
+    before = await vaultBalance();
+    await program.methods
+      .sellToken(tokens, NO_MIN_SOL_OUT, null)
+      .accountsPartial(tradeAccounts(curve, wallet))
+      .rpc();
+    assert.equal(
+      (await vaultBalance()) - before,
+      solOut.muln(100).divn(10_000).toNumber()
+    );
+
+    await expectError(setFee(10_000), "InvalidBasisPoints");
+    await setFee(0);
+  });
+
+  it("lets only the owner collect the fee vault above its rent floor", async () => {
+    const collectFees = (amount: BN, owner = wallet) =>
+      program.methods
+        .collectFees(amount)
+        .accountsPartial({ owner, globalState, feeVault });
+    await program.methods
+      .setFee(100)
+      .accountsPartial({ owner: wallet, globalState })
+      .rpc();
+    const curve = await launchCoin();
+    await createBuyerAta(curve).rpc();
+    await buy(curve, sol(1));
+    await program.methods
+      .setFee(0)
+      .accountsPartial({ owner: wallet, globalState })
+      .rpc();
+
+    const stranger = Keypair.generate();
+    await expectError(
+      collectFees(sol(0.001), stranger.publicKey).signers([stranger]).rpc(),
+      "NotOwner"
+    );
+
+    const rentExempt =
+      await provider.connection.getMinimumBalanceForRentExemption(0);
+    const available = (await provider.connection.getBalance(feeVault)) - rentExempt;
+    assert.isAtLeast(available, sol(0.01).toNumber());
+    await expectError(
+      collectFees(new BN(available + 1)).rpc(),
+      "InsufficientSolBalance"
+    );
+
+    await collectFees(new BN(available)).rpc();
+    assert.equal(await provider.connection.getBalance(feeVault), rentExempt);
+  });
+
+  it("splits collected fees between the fee recipients by share", async () => {
+    const setFeeRecipients = (
+      feeRecipients: { recipient: anchor.web3.PublicKey; shareBps: number }[]
+    ) =>
+      program.methods
+        .setFeeRecipients(feeRecipients)
+        .accountsPartial({ owner: wallet, globalState })
+        .rpc();
+    const wallets = [Keypair.generate(), Keypair.generate(), Keypair.generate()];
+    const shares = [5_000, 3_000, 2_000];
+    const recipientMetas = wallets.map((w) => ({
+      pubkey: w.publicKey,
+      isSigner: false,
+      isWritable: true,
+    }));
+
+    await expectError(
+      setFeeRecipients(
+        wallets.map((w, i) => ({ recipient: w.publicKey, shareBps: shares[i] - i }))
+      ),
+      "InvalidFeeSplit"
+    );
+    await setFeeRecipients(
+      wallets.map((w, i) => ({ recipient: w.publicKey, shareBps: shares[i] }))
+    );
+
+    await program.methods
+      .setFee(100)
+      .accountsPartial({ owner: wallet, globalState })
+      .rpc();
+    const curve = await launchCoin();
+    await createBuyerAta(curve).rpc();
+    await buy(curve, sol(3));
+    await program.methods
+      .setFee(0)
+      .accountsPartial({ owner: wallet, globalState })
+      .rpc();
+
+    const amount = sol(0.02);
+    const collectFees = (metas: anchor.web3.AccountMeta[]) =>
+      program.methods
+        .collectFees(amount)
+        .accountsPartial({ owner: wallet, globalState, feeVault })
+        .remainingAccounts(metas)
+        .rpc();
+    // every recipient must be listed, in the order they were set
+    await expectError(
+      collectFees(recipientMetas.slice(0, 2)),
+      "InvalidFeeRecipientAccounts"
+    );
+    await expectError(
+      collectFees([recipientMetas[1], recipientMetas[0], recipientMetas[2]]),
+      "InvalidFeeRecipientAccounts"
+    );
+
+    await collectFees(recipientMetas);
+    for (const [i, w] of wallets.entries()) {
+      assert.equal(
+        await provider.connection.getBalance(w.publicKey),
+        amount.muln(shares[i]).divn(10_000).toNumber()
+      );
+    }
+
+    await setFeeRecipients([]);
+  });
+
+  it("takes the buy fee in tokens kept by the curve under fee_in_token", async () => {
+    const setFee = (bps: number) =>
+      program.methods
+        .setFee(bps)
+        .accountsPartial({ owner: wallet, globalState })
+        .rpc();
+    const setFeeInToken = (feeInToken: boolean) =>
+      program.methods
+        .setFeeInToken(feeInToken)
+        .accountsPartial({ owner: wallet, globalState })
+        .rpc();
+    const [solFeeCurve, tokenFeeCurve] = [await launchCoin(), await launchCoin()];
+    const amount = sol(1);
+    // what the whole amount buys on a fresh curve with no fee
+    const { tokensOut: tokensPriced } = await program.methods
+      .quoteBuyEffective(amount)
+      .accountsPartial(queryAccounts(tokenFeeCurve))
+      .view();
+
+    await setFee(100);
+    let vaultBefore = await provider.connection.getBalance(feeVault);
+    await buy(solFeeCurve, amount);
+    const solFeeTokens = await tokenBalance(solFeeCurve);
+    assert.equal(
+      (await provider.connection.getBalance(feeVault)) - vaultBefore,
+      amount.muln(100).divn(10_000).toNumber()
+    );
+
+    await setFeeInToken(true);
+    vaultBefore = await provider.connection.getBalance(feeVault);
+    const escrowBefore = await provider.connection.getBalance(
+      tokenFeeCurve.solEscrow
+    );
+    await buy(tokenFeeCurve, amount);
+    const tokenFeeTokens = await tokenBalance(tokenFeeCurve);
+
+    // the whole SOL leg reaches the curve, which keeps 1% of the tokens it priced
+    assert.equal(await provider.connection.getBalance(feeVault), vaultBefore);
+    assert.equal(
+      (await provider.connection.getBalance(tokenFeeCurve.solEscrow)) -
+        escrowBefore,
+      amount.toNumber()
+    );
+    assert.ok(
+      tokenFeeTokens.eq(tokensPriced.sub(tokensPriced.muln(100).divn(10_000)))
+    );
+    const state = await program.account.bondingCurve.fetch(
+      tokenFeeCurve.bondingCurve
+    );
+    assert.ok(state.tokensSold.eq(tokenFeeTokens));
+    const curveBalance = await provider.connection.getTokenAccountBalance(
+      tokenFeeCurve.curveTokenAccount
+    );
+    assert.equal(
+      curveBalance.value.amount,
+      state.totalSupply.sub(tokenFeeTokens).toString()
+    );
+
+    // a SOL fee only gives up the last, most expensive tokens of the buy, so it leaves more
+    assert.ok(solFeeTokens.gt(tokenFeeTokens));
+
+    await setFeeInToken(false);
+    await setFee(0);
+  });
+
+  it("widens the spread as sell fees rise", async () => {
+    const setSellPenalty = (bps: number, seconds: number) =>
+      program.methods
+        .setSellPenalty(bps, new BN(seconds))
+        .accountsPartial({ owner: wallet, globalState })
+        .rpc();
+    const curve = await launchCoin();
+    const traderPosition = traderPositionPda(curve, wallet);
+    const spread = (position: anchor.web3.PublicKey | null) =>
+      program.methods
+        .getSpreadBps()
+        .accountsPartial({
+          ...queryAccounts(curve),
+          trader: wallet,
+          traderPosition: position,
+        })
+        .view() as Promise<BN>;
+
+    await setSellPenalty(1_000, 1_000);
+    await createBuyerAta(curve).rpc();
+    await program.methods
+      .buyToken(sol(0.1), NO_MAX_PRICE, null, null)
+      .accountsPartial({ ...tradeAccounts(curve, wallet), traderPosition })
+      .rpc();
+
+    // a fresh buyer pays the sell penalty on top of the fees every trader pays
+    const base = await spread(null);
+    assert.isAbove((await spread(traderPosition)).toNumber(), base.toNumber());
+
+    await program.methods
+      .setFee(100)
+      .accountsPartial({ owner: wallet, globalState })
+      .rpc();
+    assert.isAbove((await spread(null)).toNumber(), base.toNumber());
+
+    await program.methods
+      .setFee(0)
+      .accountsPartial({ owner: wallet, globalState })
+      .rpc();
+    await setSellPenalty(0, 0);
+  });
+
+  it("updates the curve defaults and the supply new launches start from", async () => {
+    // only the owner may change them
+    const stranger = Keypair.generate();
+    await expectError(
+      program.methods
+        .updateGlobalParams(sol(40), VIRTUAL_TOKEN_LIQUIDITY, TOTAL_TOKENS_TO_MINT)
+        .accountsPartial({ owner: stranger.publicKey, globalState })
+        .signers([stranger])
+        .rpc(),
+      "NotOwner"
+    );
+
+    const newVsol = sol(40);
+    const newVtoken = VIRTUAL_TOKEN_LIQUIDITY.muln(2);
+    const newTotal = TOTAL_TOKENS_TO_MINT.muln(2);
+
+    // the supply must leave a migration reserve beyond the sale amount
+    await expectError(
+      program.methods
+        .updateGlobalParams(newVsol, newVtoken, TOKENS_TO_SELL)
+        .accountsPartial({ owner: wallet, globalState })
+        .rpc(),
+      "SupplyReconciliationError"
+    );
+
+    await program.methods
+      .updateGlobalParams(newVsol, newVtoken, newTotal)
+      .accountsPartial({ owner: wallet, globalState })
+      .rpc();
+
+    const state = await program.account.globalState.fetch(globalState);
+    assert.ok(state.totalTokensToMint.eq(newTotal));
+    assert.ok(state.tokensToSell.eq(TOKENS_TO_SELL));
+    assert.ok(state.owner.equals(wallet));
+    const curve = await launchCoin();
+    const launched = await program.account.bondingCurve.fetch(curve.bondingCurve);
+    assert.ok(launched.virtualSolLiquidity.eq(newVsol));
+    assert.ok(launched.virtualTokenLiquidity.eq(newVtoken));
+
+    // the new supply is minted, with the sale amount kept and the reserve taking the rest
+    const minted = await provider.connection.getTokenAccountBalance(
+      curve.curveTokenAccount
+    );
+    assert.equal(minted.value.amount, newTotal.toString());
+    assert.ok(launched.tokensToSell.eq(TOKENS_TO_SELL));
+    assert.ok(launched.migrationReserve.eq(newTotal.sub(TOKENS_TO_SELL)));
+
+    await program.methods
+      .updateGlobalParams(VIRTUAL_SOL_LIQUIDITY, VIRTUAL_TOKEN_LIQUIDITY, TOTAL_TOKENS_TO_MINT)
+      .accountsPartial({ owner: wallet, globalState })
+      .rpc();
+  });
+
+  it("launches, graduates and withdraws a curve end to end", async () => {
+    const curve = await launchCoin();
+    await buy(curve, sol(1));
+    await expectError(
+      program.methods
+        .graduateAndWithdraw()
+        .accountsPartial(withdrawAccounts(curve))
+        .rpc(),
+      "SellLimitNotReached"
+    );
+
+    // large enough to hit the sell limit and deactivate the curve
+    await buy(curve, sol(100));
+    const graduated = await program.account.bondingCurve.fetch(curve.bondingCurve);
+    assert.isFalse(graduated.isActive);
+
+    // fund the treasury so it can pay a graduation bonus
+    const bonus = sol(0.5);
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        SystemProgram.transfer({
+          fromPubkey: wallet,
+          toPubkey: treasury,
+          lamports: bonus.toNumber(),
+        })
+      )
+    );
+    await program.methods
+      .setGraduationBonusSol(bonus)
+      .accountsPartial({ owner: wallet, globalState })
+      .rpc();
+
+    const walletTokensBefore = await tokenBalance(curve);
+    const treasuryBefore = await provider.connection.getBalance(treasury);
+    await program.methods
+      .graduateAndWithdraw()
+      .accountsPartial(withdrawAccounts(curve))
+      .rpc();
+
+    // the launch ATA every other instruction uses is the one emptied
+    const curveTokens = await provider.connection.getTokenAccountBalance(
+      curve.curveTokenAccount
+    );
+    assert.equal(curveTokens.value.amount, "0");
+    assert.ok(
+      (await tokenBalance(curve))
+        .sub(walletTokensBefore)
+        .eq(graduated.totalSupply.sub(graduated.tokensSold))
+    );
+    assert.equal(await provider.connection.getBalance(curve.solEscrow), 0);
+    assert.equal(
+      treasuryBefore - (await provider.connection.getBalance(treasury)),
+      bonus.toNumber()
+    );
+
+    // a migrated curve can neither trade nor be withdrawn again
+    assert.isTrue(
+      (await program.account.bondingCurve.fetch(curve.bondingCurve)).migrated
+    );
+    await expectError(buy(curve, sol(1)), "CurveMigrated");
+    await expectError(
+      program.methods.withdrawFunds().accountsPartial(withdrawAccounts(curve)).rpc(),
+      "CurveMigrated"
+    );
+
+    await program.methods
+      .setGraduationBonusSol(new BN(0))
+      .accountsPartial({ owner: wallet, globalState })
+      .rpc();
+  });
+
+  it("halts buys and sells while paused but still launches and migrates", async () => {
+    const setPaused = (paused: boolean) =>
+      program.methods
+        .setPaused(paused)
+        .accountsPartial({ owner: wallet, globalState })
+        .rpc();
+    const sell = (curve: Curve, tokens: BN) =>
+      program.methods
+        .sellToken(tokens, NO_MIN_SOL_OUT, null)
+        .accountsPartial(tradeAccounts(curve, wallet))
+        .rpc();
+    const curve = await launchCoin();
+    await buy(curve, sol(0.1));
+    const graduating = await launchCoin();
+    await buy(graduating, sol(100));
+
+    const setLaunchesPaused = (paused: boolean) =>
+      program.methods
+        .setLaunchesPaused(paused)
+        .accountsPartial({ owner: wallet, globalState })
+        .rpc();
+    const checkPauseToggled = async (
+      signature: string,
+      scope: "trading" | "launches",
+      paused: boolean
+    ) => {
+      const event = (await eventsOf(signature)).find(
+        (e) => e.name === "pauseToggled"
+      );
+      assert.ok(event);
+      assert.deepEqual(Object.keys(event.data.scope), [scope]);
+      assert.equal(event.data.paused, paused);
+    };
+
+    await checkPauseToggled(await setPaused(true), "trading", true);
+    await expectError(buy(curve, sol(0.1)), "ProtocolPaused");
+    await expectError(sell(curve, await tokenBalance(curve)), "ProtocolPaused");
+
+    // the owner can still launch and migrate a graduated curve
+    await launchCoin();
+    await program.methods
+      .withdrawFunds()
+      .accountsPartial(withdrawAccounts(graduating))
+      .rpc();
+    assert.isTrue(
+      (await program.account.bondingCurve.fetch(graduating.bondingCurve)).migrated
+    );
+
+    await checkPauseToggled(await setPaused(false), "trading", false);
+    await buy(curve, sol(0.1));
+    await sell(curve, await tokenBalance(curve));
+    assert.ok((await tokenBalance(curve)).isZero());
+
+    // pausing launches reports its own scope
+    await checkPauseToggled(await setLaunchesPaused(true), "launches", true);
+    await expectError(launchCoin(), "LaunchesPaused");
+    await checkPauseToggled(await setLaunchesPaused(false), "launches", false);
+  });
+
+  it("changes the state hash only when the curve changes", async () => {
+    const curve = await launchCoin();
+    const stateHash = async () =>
+      Buffer.from(
+        await program.methods
+          .getStateHash()
+          .accountsPartial(queryAccounts(curve))
+          .view()
+      ).toString("hex");
+
+    const launched = await stateHash();
+    assert.equal(await stateHash(), launched);
+
+    await buy(curve, sol(0.1));
+    const traded = await stateHash();
+    assert.notEqual(traded, launched);
+    assert.equal(await stateHash(), traded);
+  });
+
+  it("pays the migrator the escrow SOL plus the graduation bonus", async () => {
+    const setBonus = (bonus: BN) =>
+      program.methods
+        .setGraduationBonusSol(bonus)
+        .accountsPartial({ owner: wallet, globalState })
+        .rpc();
+    const curve = await launchCoin();
+    await buy(curve, sol(100));
+
+    // the bonus must be covered by the treasury above its rent floor
+    const rentExempt =
+      await provider.connection.getMinimumBalanceForRentExemption(0);
+    const treasuryAvailable =
+      (await provider.connection.getBalance(treasury)) - rentExempt;
+    await setBonus(new BN(treasuryAvailable + 1));
+    await expectError(
+      program.methods.withdrawFunds().accountsPartial(withdrawAccounts(curve)).rpc(),
+      "InsufficientSolBalance"
+    );
+
+    const bonus = sol(0.25);
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        SystemProgram.transfer({
+          fromPubkey: wallet,
+          toPubkey: treasury,
+          lamports: bonus.toNumber(),
+        })
+      )
+    );
+    await setBonus(bonus);
+
+    const escrow = await provider.connection.getBalance(curve.solEscrow);
+    const walletBefore = await provider.connection.getBalance(wallet);
+    const signature = await program.methods
+      .withdrawFunds()
+      .accountsPartial(withdrawAccounts(curve))
+      .rpc({ commitment: "confirmed" });
+    const tx = await provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const received =
+      (await provider.connection.getBalance(wallet)) - walletBefore + tx.meta.fee;
+    assert.equal(received, escrow + bonus.toNumber());
+
+    await setBonus(new BN(0));
+  });
+
+  it("graduates and withdraws only a sold-out curve, for the owner only", async () => {
+    const curve = await launchCoin();
+    const graduateAndWithdraw = (payer = wallet) =>
+      program.methods
+        .graduateAndWithdraw()
+        .accountsPartial({ ...withdrawAccounts(curve), payer });
+
+    await buy(curve, sol(1));
+    await expectError(graduateAndWithdraw().rpc(), "SellLimitNotReached");
+
+    await buy(curve, sol(100));
+    const stranger = Keypair.generate();
+    await expectError(
+      graduateAndWithdraw(stranger.publicKey).signers([stranger]).rpc(),
+      "NotOwner"
+    );
+
+    // the completing buy already ended trading, so the curve is counted out once
+    const activeBefore = (await program.account.globalState.fetch(globalState))
+      .activeCurveCount;
+    await graduateAndWithdraw().rpc();
+    const after = await program.account.bondingCurve.fetch(curve.bondingCurve);
+    assert.isFalse(after.isActive);
+    assert.isTrue(after.migrated);
+    assert.ok(
+      (await program.account.globalState.fetch(globalState)).activeCurveCount.eq(
+        activeBefore
+      )
+    );
+  });
+
+  it("ends trading through deactivate_curve and counts the curve out", async () => {
+    const curve = await launchCoin();
+    await buy(curve, sol(1));
+    const activeBefore = (await program.account.globalState.fetch(globalState))
+      .activeCurveCount;
+
+    await program.methods
+      .deactivateCurve()
+      .accountsPartial({ owner: wallet, ...queryAccounts(curve) })
+      .rpc();
 
-  it("Is initialized!", async () => {
-    // Add your test here.
-    const tx = await program.methods.initialize().rpc();
-    console.log("Your transaction signature", tx);
+    assert.isFalse(
+      (await program.account.bondingCurve.fetch(curve.bondingCurve)).isActive
+    );
+    assert.ok(
+      (await program.account.globalState.fetch(globalState)).activeCurveCount.eq(
+        activeBefore.subn(1)
+      )
+    );
+    // trading has already ended, so a second call is rejected
+    await expectError(
+      program.methods
+        .deactivateCurve()
+        .accountsPartial({ owner: wallet, ...queryAccounts(curve) })
+        .rpc(),
+      "BondingCurveNotActive"
+    );
   });
 });